@@ -0,0 +1,131 @@
+// converts every PNG in `assets` into an `image565` image in `OUT_DIR`, in whichever encoding is the
+// smallest; transparent pixels are blended over black, the background of `Term`, unless the name gives
+// the RGB565 color the image is drawn on, like `logo.bg-0010.png` for `logo.img`
+//
+// every PNG in `assets/fonts` is a sheet of 16 x 6 glyphs, ' ' to 127, packed into the Rust source of
+// an anti-aliased font for `term::font`, with the bits per pixel in its name like `heading.4bpp.png`
+use std::{convert::TryFrom, env, fmt::Write, fs, fs::File, path::Path};
+
+fn read_rgba(png: &Path) -> (u32, u32, Vec<u8>) {
+    let mut decoder = png::Decoder::new(File::open(png).unwrap());
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    let rgba = buffer[..info.buffer_size()]
+        .chunks_exact(info.color_type.samples())
+        .flat_map(|pixel| match info.color_type {
+            png::ColorType::Grayscale => [pixel[0], pixel[0], pixel[0], 255],
+            png::ColorType::GrayscaleAlpha => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            png::ColorType::Rgb => [pixel[0], pixel[1], pixel[2], 255],
+            png::ColorType::Rgba => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            png::ColorType::Indexed => unreachable!("expanded by normalize_to_color8"),
+        })
+        .collect();
+    (info.width, info.height, rgba)
+}
+
+fn convert(png: &Path, background: u16, out: &Path) {
+    let (width, height, rgba) = read_rgba(png);
+    let (r, g, b) = (background >> 11, background >> 5 & 0x3f, background & 0x1f);
+    let background = [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2].map(|channel| u8::try_from(channel).unwrap());
+    let pixels = image565::encode::from_rgba8(&rgba, background);
+    let (width, height) = (u16::try_from(width).unwrap(), u16::try_from(height).unwrap());
+    fs::write(out, image565::encode::encode_smallest(width, height, &pixels)).unwrap();
+}
+
+// the coverage of a pixel is its brightness times its alpha; the blank columns and rows around each
+// glyph are cut off, and glyphs advance by their width and an eighth of the cell
+fn pack_font(png: &Path, depth: u32, out: &Path) {
+    let depth_name = match depth {
+        2 => "Two",
+        4 => "Four",
+        _ => panic!("{}: fonts have 2 or 4 bits per pixel", png.display()),
+    };
+    let (width, height, rgba) = read_rgba(png);
+    let (cell_width, cell_height) = (width / 16, height / 6);
+    let max = (1 << depth) - 1;
+    let level = |x: u32, y: u32| {
+        let pixel = &rgba[usize::try_from((y * width + x) * 4).unwrap()..][..4];
+        let brightness = (u32::from(pixel[0]) + u32::from(pixel[1]) + u32::from(pixel[2])) / 3;
+        (brightness * u32::from(pixel[3]) / 255 * max + 127) / 255
+    };
+    let mut glyphs = String::new();
+    let mut data = Vec::new();
+    for index in 0..96 {
+        let (cell_x, cell_y) = (index % 16 * cell_width, index / 16 * cell_height);
+        let inked = |x: u32, y: u32| level(cell_x + x, cell_y + y) > 0;
+        let columns: Vec<u32> = (0..cell_width).filter(|&x| (0..cell_height).any(|y| inked(x, y))).collect();
+        let rows: Vec<u32> = (0..cell_height).filter(|&y| (0..cell_width).any(|x| inked(x, y))).collect();
+        let (left, glyph_width, top, glyph_height, advance) = match (columns.first(), columns.last(), rows.first(), rows.last()) {
+            (Some(&left), Some(&right), Some(&top), Some(&bottom)) => {
+                (left, right - left + 1, top, bottom - top + 1, right - left + 1 + cell_width / 8)
+            }
+            _ => (0, 0, 0, 0, cell_width / 2),
+        };
+        let offset = data.len();
+        let (mut packed, mut bits) = (0, 0);
+        for y in top..top + glyph_height {
+            for x in left..left + glyph_width {
+                packed |= level(cell_x + x, cell_y + y) << bits;
+                bits += depth;
+                if bits == 8 {
+                    data.push(u8::try_from(packed).unwrap());
+                    packed = 0;
+                    bits = 0;
+                }
+            }
+        }
+        if bits > 0 {
+            data.push(u8::try_from(packed).unwrap());
+        }
+        writeln!(
+            glyphs,
+            "    PackedGlyph {{ offset: {}, width: {}, height: {}, top: {}, advance: {} }},",
+            offset, glyph_width, glyph_height, top, advance
+        )
+        .unwrap();
+    }
+    let mut source = String::new();
+    writeln!(source, "// packed by build.rs from {}", png.display()).unwrap();
+    writeln!(source, "pub const DEPTH: Depth = Depth::{};", depth_name).unwrap();
+    writeln!(source, "pub const LINE_HEIGHT: u8 = {};", cell_height).unwrap();
+    writeln!(source, "pub static GLYPHS: [PackedGlyph; 96] = [\n{}];", glyphs).unwrap();
+    writeln!(source, "pub static DATA: [u8; {}] = {:?};", data.len(), data).unwrap();
+    fs::write(out, source).unwrap();
+}
+
+fn pngs(dir: &str) -> impl Iterator<Item = std::path::PathBuf> {
+    println!("cargo:rerun-if-changed={}", dir);
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .inspect(|path| println!("cargo:rerun-if-changed={}", path.display()))
+}
+
+fn main() {
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    for path in pngs("assets") {
+        // `logo.bg-0010.png` is `logo.img`
+        let name = Path::new(path.file_stem().unwrap());
+        let (name, background) = match name.extension().and_then(|bg| bg.to_str()?.strip_prefix("bg-")) {
+            Some(hex) => {
+                let background = u16::from_str_radix(hex, 16).unwrap_or_else(|_| panic!("{}: the background is RGB565 in hex", path.display()));
+                (name.with_extension(""), background)
+            }
+            None => (name.to_path_buf(), 0),
+        };
+        convert(&path, background, &Path::new(&out_dir).join(name.with_extension("img")));
+    }
+    for path in pngs("assets/fonts") {
+        // `heading.4bpp.png` is `heading.rs` with 4 bits per pixel
+        let name = Path::new(path.file_stem().unwrap());
+        let depth = name
+            .extension()
+            .and_then(|bpp| bpp.to_str()?.strip_suffix("bpp")?.parse().ok())
+            .unwrap_or_else(|| panic!("{}: no bits per pixel in the name", path.display()));
+        let out = Path::new(&out_dir).join(name.with_extension("rs"));
+        pack_font(&path, depth, &out);
+    }
+}
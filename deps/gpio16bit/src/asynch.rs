@@ -0,0 +1,370 @@
+//! Async counterparts of `WriteOnlyInterface`/`ReadWriteInterface` for async executors (e.g. embassy).
+//!
+//! Commands and reads are short and stay synchronous on the underlying interface, only bulk data
+//! writes are awaited on a `BulkWrite` peripheral (DMA, timer driven WR, ...). embedded-hal-async
+//! has no trait for a parallel bus, so the bus traits are this crate's own, the waiting in the
+//! reset sequence is done on an `embedded_hal_async::delay::DelayNs`.
+#![allow(async_fn_in_trait)]
+
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{Gpio16BitInterface, ReadWriteInterface, WriteOnlyInterface};
+
+pub trait AsyncWriteOnlyInterface {
+    type Error;
+    async fn command(&mut self, value: u16) -> Result<(), Self::Error>;
+    async fn data(&mut self, values: &[u16]) -> Result<(), Self::Error>;
+    async fn fill(&mut self, value: u16, count: u32) -> Result<(), Self::Error>;
+}
+
+pub trait AsyncReadWriteInterface: AsyncWriteOnlyInterface {
+    async fn read_data(&mut self, values: &mut [u16]) -> Result<(), Self::Error>;
+}
+
+/// Peripheral that streams data words onto the bus (including the WR strobe) without the CPU.
+///
+/// DC and CS are already set up when the transfer starts, the returned future must not resolve
+/// before the last word has been strobed out.
+pub trait BulkWrite {
+    type Error;
+    async fn write(&mut self, values: &[u16]) -> Result<(), Self::Error>;
+    async fn fill(&mut self, value: u16, count: u32) -> Result<(), Self::Error>;
+    // stops a transfer whose future was dropped before it completed, does nothing when none is running
+    fn abort(&mut self);
+}
+
+#[derive(Debug)]
+pub enum Error<BusError, TransferError> {
+    Bus(BusError),
+    Transfer(TransferError),
+}
+
+impl<Bus, CS, RST, PWR, Error> Gpio16BitInterface<Bus, CS, RST, PWR>
+where
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    RST: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    PWR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+{
+    /// `hard_reset` that lets other tasks run while waiting for the controller.
+    pub async fn hard_reset_async<Delay: DelayNs>(&mut self, delay: &mut Delay) -> Result<(), Error> {
+        self.reset.set_high()?;
+        delay.delay_us(self.reset_timing.pulse_us).await;
+        self.reset.set_low()?;
+        delay.delay_us(self.reset_timing.pulse_us).await;
+        self.reset.set_high()?;
+        delay.delay_us(self.reset_timing.recovery_us).await;
+        Ok(())
+    }
+}
+
+fn command<I: WriteOnlyInterface>(interface: &mut I, value: u16) -> Result<(), I::Error> {
+    let mut writer = interface.write()?;
+    let mut setter = writer.command()?;
+    setter.set_value(value)?.commit()
+}
+
+/// Runs a synchronous interface under an async executor, every call completes before it returns.
+///
+/// For controllers without a `BulkWrite` peripheral, nothing else runs while data is bit-banged.
+pub struct Blocking<I> {
+    interface: I,
+}
+
+impl<I: WriteOnlyInterface> Blocking<I> {
+    pub fn new(interface: I) -> Self {
+        Self { interface }
+    }
+
+    pub fn release(self) -> I {
+        self.interface
+    }
+}
+
+impl<I: WriteOnlyInterface> AsyncWriteOnlyInterface for Blocking<I> {
+    type Error = I::Error;
+
+    async fn command(&mut self, value: u16) -> Result<(), I::Error> {
+        command(&mut self.interface, value)
+    }
+
+    async fn data(&mut self, values: &[u16]) -> Result<(), I::Error> {
+        let mut writer = self.interface.write()?;
+        let mut setter = writer.data()?;
+        for &value in values {
+            setter.set_value(value)?.commit()?;
+        }
+        Ok(())
+    }
+
+    async fn fill(&mut self, value: u16, count: u32) -> Result<(), I::Error> {
+        let mut writer = self.interface.write()?;
+        let mut setter = writer.data()?;
+        for _ in 0..count {
+            setter.set_value(value)?.commit()?;
+        }
+        Ok(())
+    }
+}
+
+impl<I: ReadWriteInterface> AsyncReadWriteInterface for Blocking<I> {
+    async fn read_data(&mut self, values: &mut [u16]) -> Result<(), I::Error> {
+        let mut reader = self.interface.read()?;
+        let mut getter = reader.data()?;
+        for value in values {
+            *value = getter.get_value()?;
+        }
+        Ok(())
+    }
+}
+
+// aborts the transfer unless it got to complete; declared after the `Writer`, so when a future is
+// dropped in the middle of a transfer the bus is stopped before CS is released
+struct Running<'a, Bulk: BulkWrite> {
+    bulk: &'a mut Bulk,
+    complete: bool,
+}
+
+impl<'a, Bulk: BulkWrite> Running<'a, Bulk> {
+    fn new(bulk: &'a mut Bulk) -> Self {
+        Self { bulk, complete: false }
+    }
+
+    fn complete<T>(mut self, result: T) -> T {
+        self.complete = true;
+        result
+    }
+}
+
+impl<'a, Bulk: BulkWrite> Drop for Running<'a, Bulk> {
+    fn drop(&mut self) {
+        if !self.complete {
+            self.bulk.abort();
+        }
+    }
+}
+
+/// Sends commands and reads through the synchronous interface and hands data writes to `Bulk`.
+pub struct Offloaded<I, Bulk> {
+    interface: I,
+    bulk: Bulk,
+}
+
+impl<I: WriteOnlyInterface, Bulk: BulkWrite> Offloaded<I, Bulk> {
+    pub fn new(interface: I, bulk: Bulk) -> Self {
+        Self { interface, bulk }
+    }
+
+    pub fn release(self) -> (I, Bulk) {
+        (self.interface, self.bulk)
+    }
+}
+
+impl<I: WriteOnlyInterface, Bulk: BulkWrite> AsyncWriteOnlyInterface for Offloaded<I, Bulk> {
+    type Error = Error<I::Error, Bulk::Error>;
+
+    async fn command(&mut self, value: u16) -> Result<(), Self::Error> {
+        command(&mut self.interface, value).map_err(Error::Bus)
+    }
+
+    async fn data(&mut self, values: &[u16]) -> Result<(), Self::Error> {
+        // the writer keeps CS asserted and DC high until the transfer completes or is dropped
+        let mut writer = self.interface.write().map_err(Error::Bus)?;
+        writer.data().map_err(Error::Bus)?;
+        let running = Running::new(&mut self.bulk);
+        let result = running.bulk.write(values).await;
+        running.complete(result).map_err(Error::Transfer)
+    }
+
+    async fn fill(&mut self, value: u16, count: u32) -> Result<(), Self::Error> {
+        let mut writer = self.interface.write().map_err(Error::Bus)?;
+        writer.data().map_err(Error::Bus)?;
+        let running = Running::new(&mut self.bulk);
+        let result = running.bulk.fill(value, count).await;
+        running.complete(result).map_err(Error::Transfer)
+    }
+}
+
+impl<I: ReadWriteInterface, Bulk: BulkWrite> AsyncReadWriteInterface for Offloaded<I, Bulk> {
+    async fn read_data(&mut self, values: &mut [u16]) -> Result<(), Self::Error> {
+        let mut reader = self.interface.read().map_err(Error::Bus)?;
+        let mut getter = reader.data().map_err(Error::Bus)?;
+        for value in values {
+            *value = getter.get_value().map_err(Error::Bus)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        future::Future,
+        pin::{pin, Pin},
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+    use crate::{
+        mock::{Delay, Event, Log},
+        GpioWriteOnly16BitInterface, ResetTiming,
+    };
+
+    fn poll<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+        future.poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = poll(future.as_mut()) {
+                return output;
+            }
+        }
+    }
+
+    // pending once, like a transfer that is still going when first polled
+    struct Started(bool);
+
+    impl Future for Started {
+        type Output = ();
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+
+    // records the words it sends once they are done
+    struct Bulk(Log);
+
+    impl BulkWrite for Bulk {
+        type Error = ();
+        async fn write(&mut self, values: &[u16]) -> Result<(), ()> {
+            Started(false).await;
+            values.iter().for_each(|&value| self.0.push(Event::Value(value)));
+            Ok(())
+        }
+        async fn fill(&mut self, value: u16, count: u32) -> Result<(), ()> {
+            Started(false).await;
+            (0..count).for_each(|_| self.0.push(Event::Value(value)));
+            Ok(())
+        }
+        fn abort(&mut self) {
+            self.0.push(Event::Abort);
+        }
+    }
+
+    fn offloaded(log: &Log) -> Offloaded<impl WriteOnlyInterface<Error = ()>, Bulk> {
+        let interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_cs(log.pin("cs"))
+            .unwrap();
+        log.take();
+        Offloaded::new(interface, Bulk(log.clone()))
+    }
+
+    #[test]
+    fn commands_are_bit_banged() {
+        let log = Log::default();
+        let mut bus = offloaded(&log);
+        block_on(bus.command(0x2c)).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Event::Pin("wr", true),
+                Event::Pin("cs", false),
+                Event::Pin("dc", false),
+                Event::Value(0x2c),
+                Event::Pin("wr", false),
+                Event::Pin("wr", true),
+                Event::Pin("cs", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn data_is_offloaded_while_selected() {
+        let log = Log::default();
+        let mut bus = offloaded(&log);
+        block_on(bus.data(&[1, 2])).unwrap();
+        block_on(bus.fill(3, 2)).unwrap();
+        let transfer = |a, b| {
+            [
+                Event::Pin("wr", true),
+                Event::Pin("cs", false),
+                Event::Pin("dc", true),
+                Event::Value(a),
+                Event::Value(b),
+                Event::Pin("cs", true),
+            ]
+        };
+        assert_eq!(log.take(), [transfer(1, 2), transfer(3, 3)].concat());
+    }
+
+    #[test]
+    fn dropped_transfer_is_aborted_before_cs_is_released() {
+        let log = Log::default();
+        let mut bus = offloaded(&log);
+        {
+            let mut future = pin!(bus.fill(0xffff, 800 * 480));
+            assert!(poll(future.as_mut()).is_pending());
+        }
+        assert_eq!(
+            log.take(),
+            [
+                Event::Pin("wr", true),
+                Event::Pin("cs", false),
+                Event::Pin("dc", true),
+                Event::Abort,
+                Event::Pin("cs", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocking_data_is_strobed_word_by_word() {
+        let log = Log::default();
+        let interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"));
+        let mut bus = Blocking::new(interface);
+        block_on(bus.data(&[7, 8])).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Event::Pin("wr", true),
+                Event::Pin("dc", true),
+                Event::Value(7),
+                Event::Pin("wr", false),
+                Event::Pin("wr", true),
+                Event::Value(8),
+                Event::Pin("wr", false),
+                Event::Pin("wr", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn async_hard_reset_waits_on_the_delay() {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_reset(log.pin("reset"))
+            .unwrap()
+            .reset_timing(ResetTiming {
+                pulse_us: 10,
+                recovery_us: 5_000,
+            });
+        log.take();
+        block_on(interface.hard_reset_async(&mut Delay(log.clone()))).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Event::Pin("reset", true),
+                Event::Delay(10),
+                Event::Pin("reset", false),
+                Event::Delay(10),
+                Event::Pin("reset", true),
+                Event::Delay(5_000),
+            ]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! `display-interface` support, so drivers written against `WriteOnlyDataCommand` can use this bus.
+//!
+//! Every value is transferred as a single bus cycle: `u8` values are zero extended and the
+//! endianness of the `U16BE`/`U16LE` variants is ignored, since it only describes how a `u16` is
+//! split into bytes on 8-bit buses.
+
+use ::display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::{Gpio16BitInterface, WriteOnlyInterface};
+
+fn send<I: WriteOnlyInterface>(interface: &mut I, command: bool, values: DataFormat<'_>) -> Result<(), DisplayError> {
+    let mut writer = interface.write().map_err(|_| DisplayError::BusWriteError)?;
+    let mut setter = if command { writer.command() } else { writer.data() }.map_err(|_| DisplayError::DCError)?;
+    let mut write = |value: u16| {
+        setter
+            .set_value(value)
+            .and_then(|mut c| c.commit())
+            .map_err(|_| DisplayError::BusWriteError)
+    };
+    match values {
+        DataFormat::U8(values) => values.iter().try_for_each(|&value| write(u16::from(value))),
+        DataFormat::U16(values) => values.iter().try_for_each(|&value| write(value)),
+        DataFormat::U16BE(values) | DataFormat::U16LE(values) => values.iter().try_for_each(|&value| write(value)),
+        DataFormat::U8Iter(values) => {
+            for value in values {
+                write(u16::from(value))?;
+            }
+            Ok(())
+        }
+        DataFormat::U16BEIter(values) | DataFormat::U16LEIter(values) => {
+            for value in values {
+                write(value)?;
+            }
+            Ok(())
+        }
+        _ => Err(DisplayError::DataFormatNotImplemented),
+    }
+}
+
+/// Exposes any `WriteOnlyInterface` implementation as a `WriteOnlyDataCommand`.
+pub struct DataCommand<I>(I);
+
+impl<I: WriteOnlyInterface> DataCommand<I> {
+    pub fn new(interface: I) -> Self {
+        Self(interface)
+    }
+
+    pub fn release(self) -> I {
+        self.0
+    }
+}
+
+impl<I: WriteOnlyInterface> WriteOnlyDataCommand for DataCommand<I> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(&mut self.0, true, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(&mut self.0, false, buf)
+    }
+}
+
+impl<Bus, CS, RST, PWR> WriteOnlyDataCommand for Gpio16BitInterface<Bus, CS, RST, PWR>
+where
+    Self: WriteOnlyInterface,
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, true, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, false, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{
+        mock::{Event, Log},
+        GpioWriteOnly16BitInterface,
+    };
+
+    // the words on the bus with the level of DC while each was strobed, checking every word gets a
+    // WR pulse and the transfer happens with CS asserted
+    fn words(events: &[Event]) -> Vec<(bool, u16)> {
+        assert_eq!(events.first(), Some(&Event::Pin("wr", true)));
+        assert_eq!(events.get(1), Some(&Event::Pin("cs", false)));
+        assert_eq!(events.last(), Some(&Event::Pin("cs", true)));
+        let mut dc = None;
+        let mut words = Vec::new();
+        let mut events = events[2..events.len() - 1].iter();
+        while let Some(event) = events.next() {
+            match *event {
+                Event::Pin("dc", level) => dc = Some(level),
+                Event::Value(value) => {
+                    assert_eq!(events.next(), Some(&Event::Pin("wr", false)));
+                    assert_eq!(events.next(), Some(&Event::Pin("wr", true)));
+                    words.push((dc.expect("DC set before the first word"), value));
+                }
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+        words
+    }
+
+    fn send(command: bool, values: DataFormat<'_>) -> Vec<(bool, u16)> {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_cs(log.pin("cs"))
+            .unwrap();
+        log.take();
+        if command {
+            interface.send_commands(values).unwrap();
+        } else {
+            interface.send_data(values).unwrap();
+        }
+        words(&log.take())
+    }
+
+    #[test]
+    fn commands_are_sent_with_dc_low() {
+        assert_eq!(send(true, DataFormat::U8(&[0x2c, 0x01])), [(false, 0x2c), (false, 0x01)]);
+        assert_eq!(send(true, DataFormat::U16(&[0x2c00, 0x0001])), [(false, 0x2c00), (false, 0x0001)]);
+    }
+
+    #[test]
+    fn data_is_sent_with_dc_high_in_order() {
+        let expected = [(true, 0x1234), (true, 0xabcd), (true, 0x0001)];
+        assert_eq!(send(false, DataFormat::U16(&[0x1234, 0xabcd, 0x0001])), expected);
+        assert_eq!(send(false, DataFormat::U16BE(&mut [0x1234, 0xabcd, 0x0001])), expected);
+        assert_eq!(send(false, DataFormat::U16LE(&mut [0x1234, 0xabcd, 0x0001])), expected);
+        assert_eq!(
+            send(false, DataFormat::U16BEIter(&mut [0x1234, 0xabcd, 0x0001].iter().copied())),
+            expected
+        );
+        assert_eq!(
+            send(false, DataFormat::U16LEIter(&mut [0x1234, 0xabcd, 0x0001].iter().copied())),
+            expected
+        );
+    }
+
+    #[test]
+    fn bytes_are_zero_extended() {
+        let expected = [(true, 0x00ff), (true, 0x0012)];
+        assert_eq!(send(false, DataFormat::U8(&[0xff, 0x12])), expected);
+        assert_eq!(send(false, DataFormat::U8Iter(&mut [0xff, 0x12].iter().copied())), expected);
+    }
+
+    #[test]
+    fn empty_transfers_only_select() {
+        assert_eq!(send(false, DataFormat::U16(&[])), []);
+    }
+
+    // every format of display-interface 0.5 can be sent, so `DataFormatNotImplemented` is only left for
+    // formats added later; what can go wrong now are the pins
+    #[test]
+    fn pin_errors_are_reported() {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.failing_pin("dc"), log.pin("wr"));
+        assert!(matches!(interface.send_commands(DataFormat::U8(&[0x2c])), Err(DisplayError::DCError)));
+        let mut interface = DataCommand::new(GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.failing_pin("wr")));
+        assert!(matches!(interface.send_data(DataFormat::U16(&[0])), Err(DisplayError::BusWriteError)));
+    }
+}
@@ -0,0 +1,46 @@
+//! Adapters that let embedded-hal 1.0 drivers be used where this crate expects the 0.2 traits.
+
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::OutputPin};
+use embedded_hal_1::{delay::DelayNs, digital};
+
+/// Wraps an embedded-hal 1.0 output pin so it can be used as DC, WR, RD, CS, RESET or power pin.
+pub struct Pin<P>(P);
+
+impl<P: digital::OutputPin> Pin<P> {
+    pub fn new(pin: P) -> Self {
+        Self(pin)
+    }
+
+    pub fn release(self) -> P {
+        self.0
+    }
+}
+
+impl<P: digital::OutputPin> OutputPin for Pin<P> {
+    type Error = <P as digital::ErrorType>::Error;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low()
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high()
+    }
+}
+
+/// Wraps an embedded-hal 1.0 delay so it can drive `hard_reset`.
+pub struct Delay<D>(D);
+
+impl<D: DelayNs> Delay<D> {
+    pub fn new(delay: D) -> Self {
+        Self(delay)
+    }
+
+    pub fn release(self) -> D {
+        self.0
+    }
+}
+
+impl<D: DelayNs> DelayUs<u32> for Delay<D> {
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+}
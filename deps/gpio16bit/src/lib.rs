@@ -1,5 +1,19 @@
 #![no_std]
 
+#[cfg(feature = "async")]
+pub mod asynch;
+#[cfg(feature = "display-interface")]
+pub mod display_interface;
+#[cfg(feature = "eh1")]
+pub mod eh1;
+#[cfg(test)]
+mod mock;
+pub mod pins;
+
+use core::marker::PhantomData;
+
+use embedded_hal::blocking::delay::DelayUs;
+
 pub trait WritePort {
     fn set_value(&mut self, value: u16);
 }
@@ -10,6 +24,59 @@ pub trait ReadWritePort: WritePort {
     fn dir_read(&mut self);
 }
 
+/// Placeholder for an optional pin that is not wired, every operation is a no-op.
+pub struct NoPin<Error>(PhantomData<Error>);
+
+impl<Error> NoPin<Error> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Error> Default for NoPin<Error> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Error> embedded_hal::digital::v2::OutputPin for NoPin<Error> {
+    type Error = Error;
+    fn set_low(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Keeps the (active low) chip select asserted until dropped.
+pub struct ChipSelect<'a, CS, Error>
+where
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+{
+    cs: &'a mut CS,
+}
+
+impl<'a, CS, Error> ChipSelect<'a, CS, Error>
+where
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+{
+    fn assert(cs: &'a mut CS) -> Result<Self, Error> {
+        cs.set_low()?;
+        Ok(Self { cs })
+    }
+}
+
+impl<'a, CS, Error> Drop for ChipSelect<'a, CS, Error>
+where
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+{
+    fn drop(&mut self) {
+        // there is no way to report the error from drop, the next assert will retry anyway
+        self.cs.set_high().ok();
+    }
+}
+
 #[must_use]
 pub struct Committer<'a, WR, Error>
 where
@@ -49,23 +116,36 @@ where
     }
 }
 
-pub struct Writer<'a, PortX, DC, WR, Error>
+pub struct Writer<'a, PortX, DC, WR, CS, Error>
 where
     PortX: WritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     port: &'a mut PortX,
     dc: &'a mut DC,
     wr: &'a mut WR,
+    _cs: ChipSelect<'a, CS, Error>,
 }
 
-impl<'a, PortX, DC, WR, Error> Writer<'a, PortX, DC, WR, Error>
+impl<'a, PortX, DC, WR, CS, Error> Writer<'a, PortX, DC, WR, CS, Error>
 where
     PortX: WritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
+    // asserts CS until the writer is dropped
+    pub fn new(port: &'a mut PortX, dc: &'a mut DC, wr: &'a mut WR, cs: &'a mut CS) -> Result<Self, Error> {
+        Ok(Self {
+            port,
+            dc,
+            wr,
+            _cs: ChipSelect::assert(cs)?,
+        })
+    }
+
     pub fn command(&mut self) -> Result<ValueSetter<PortX, WR, Error>, Error> {
         self.dc.set_low()?;
         Ok(ValueSetter {
@@ -83,19 +163,23 @@ where
     }
 }
 
-pub struct ValueGetter<'a, PortX, RD, Error>
+pub struct ValueGetter<'a, PortX, RD, CS, Error>
 where
     PortX: ReadWritePort,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     port: &'a mut PortX,
     rd: &'a mut RD,
+    // only set when the getter outlives the `Reader` it was created from
+    _cs: Option<ChipSelect<'a, CS, Error>>,
 }
 
-impl<'a, PortX, RD, Error> ValueGetter<'a, PortX, RD, Error>
+impl<'a, PortX, RD, CS, Error> ValueGetter<'a, PortX, RD, CS, Error>
 where
     PortX: ReadWritePort,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     pub fn get_value(&mut self) -> Result<u16, Error> {
         let value = self.port.get_value();
@@ -105,31 +189,45 @@ where
     }
 }
 
-pub struct Reader<'a, PortX, DC, RD, Error>
+pub struct Reader<'a, PortX, DC, RD, CS, Error>
 where
     PortX: ReadWritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     port: &'a mut PortX,
     dc: &'a mut DC,
     rd: &'a mut RD,
+    cs: ChipSelect<'a, CS, Error>,
 }
 
-impl<'a, PortX, DC, RD, Error> Reader<'a, PortX, DC, RD, Error>
+impl<'a, PortX, DC, RD, CS, Error> Reader<'a, PortX, DC, RD, CS, Error>
 where
     PortX: ReadWritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
-    pub fn command(&mut self) -> Result<ValueGetter<PortX, RD, Error>, Error> {
+    // asserts CS until the reader (or a getter it was turned into) is dropped
+    pub fn new(port: &'a mut PortX, dc: &'a mut DC, rd: &'a mut RD, cs: &'a mut CS) -> Result<Self, Error> {
+        Ok(Self {
+            port,
+            dc,
+            rd,
+            cs: ChipSelect::assert(cs)?,
+        })
+    }
+
+    pub fn command(&mut self) -> Result<ValueGetter<PortX, RD, CS, Error>, Error> {
         self.dc.set_low()?;
         Ok(ValueGetter {
             port: self.port,
             rd: self.rd,
+            _cs: None,
         })
     }
-    pub fn into_command<'b>(self) -> Result<ValueGetter<'b, PortX, RD, Error>, Error>
+    pub fn into_command<'b>(self) -> Result<ValueGetter<'b, PortX, RD, CS, Error>, Error>
     where
         'a: 'b,
     {
@@ -137,17 +235,19 @@ where
         Ok(ValueGetter {
             port: self.port,
             rd: self.rd,
+            _cs: Some(self.cs),
         })
     }
 
-    pub fn data(&mut self) -> Result<ValueGetter<PortX, RD, Error>, Error> {
+    pub fn data(&mut self) -> Result<ValueGetter<PortX, RD, CS, Error>, Error> {
         self.dc.set_high()?;
         Ok(ValueGetter {
             port: self.port,
             rd: self.rd,
+            _cs: None,
         })
     }
-    pub fn into_data<'b>(self) -> Result<ValueGetter<'b, PortX, RD, Error>, Error>
+    pub fn into_data<'b>(self) -> Result<ValueGetter<'b, PortX, RD, CS, Error>, Error>
     where
         'a: 'b,
     {
@@ -155,6 +255,7 @@ where
         Ok(ValueGetter {
             port: self.port,
             rd: self.rd,
+            _cs: Some(self.cs),
         })
     }
 }
@@ -164,116 +265,417 @@ pub trait WriteOnlyInterface {
     type Error;
     type DC: embedded_hal::digital::v2::OutputPin<Error = Self::Error>;
     type WR: embedded_hal::digital::v2::OutputPin<Error = Self::Error>;
-    fn write(&mut self) -> Result<Writer<Self::Port, Self::DC, Self::WR, Self::Error>, Self::Error>;
+    type CS: embedded_hal::digital::v2::OutputPin<Error = Self::Error>;
+    fn write(&mut self) -> Result<InterfaceWriter<'_, Self>, Self::Error>;
 }
 
 pub trait ReadWriteInterface: WriteOnlyInterface {
     type Port: ReadWritePort;
     type RD: embedded_hal::digital::v2::OutputPin<Error = Self::Error>;
-    fn read(&mut self) -> Result<Reader<<Self as ReadWriteInterface>::Port, Self::DC, Self::RD, Self::Error>, Self::Error>;
+    fn read(&mut self) -> Result<InterfaceReader<'_, Self>, Self::Error>;
+}
+
+/// The `Writer` a `WriteOnlyInterface` hands out.
+pub type InterfaceWriter<'a, I> = Writer<
+    'a,
+    <I as WriteOnlyInterface>::Port,
+    <I as WriteOnlyInterface>::DC,
+    <I as WriteOnlyInterface>::WR,
+    <I as WriteOnlyInterface>::CS,
+    <I as WriteOnlyInterface>::Error,
+>;
+
+/// The `Reader` a `ReadWriteInterface` hands out.
+pub type InterfaceReader<'a, I> = Reader<
+    'a,
+    <I as ReadWriteInterface>::Port,
+    <I as WriteOnlyInterface>::DC,
+    <I as ReadWriteInterface>::RD,
+    <I as WriteOnlyInterface>::CS,
+    <I as WriteOnlyInterface>::Error,
+>;
+
+/// Durations of the hardware reset sequence in microseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResetTiming {
+    /// how long RESET is held low
+    pub pulse_us: u32,
+    /// how long to wait after RESET is released before the controller accepts commands
+    pub recovery_us: u32,
+}
+
+impl Default for ResetTiming {
+    fn default() -> Self {
+        Self {
+            pulse_us: 10,
+            recovery_us: 5_000,
+        }
+    }
 }
 
-pub struct GpioWriteOnly16BitInterface<Port, DC, WR> {
+/// The pins of a bus that can only be written.
+pub struct WriteOnlyBus<Port, DC, WR> {
     port: Port,
     dc: DC,
     wr: WR,
 }
 
-impl<PortX, DC, WR, Error> GpioWriteOnly16BitInterface<PortX, DC, WR>
+/// The pins of a bus that can be read back.
+pub struct ReadWriteBus<Port, DC, WR, RD> {
+    port: Port,
+    dc: DC,
+    wr: WR,
+    rd: RD,
+}
+
+/// A bus with its optional chip select, reset and power pins, which start out as `NoPin` and are
+/// added with `with_cs`, `with_reset` and `with_power`.
+pub struct Gpio16BitInterface<Bus, CS, RST, PWR> {
+    bus: Bus,
+    cs: CS,
+    reset: RST,
+    power: PWR,
+    reset_timing: ResetTiming,
+}
+
+pub type GpioWriteOnly16BitInterface<Port, DC, WR, CS, RST, PWR> = Gpio16BitInterface<WriteOnlyBus<Port, DC, WR>, CS, RST, PWR>;
+pub type GpioReadWrite16BitInterface<Port, DC, WR, RD, CS, RST, PWR> = Gpio16BitInterface<ReadWriteBus<Port, DC, WR, RD>, CS, RST, PWR>;
+
+impl<Bus, Error> Gpio16BitInterface<Bus, NoPin<Error>, NoPin<Error>, NoPin<Error>> {
+    fn from_bus(bus: Bus) -> Self {
+        Self {
+            bus,
+            cs: NoPin::new(),
+            reset: NoPin::new(),
+            power: NoPin::new(),
+            reset_timing: ResetTiming::default(),
+        }
+    }
+}
+
+impl<PortX, DC, WR, Error> GpioWriteOnly16BitInterface<PortX, DC, WR, NoPin<Error>, NoPin<Error>, NoPin<Error>>
 where
     PortX: WritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     pub fn new(port: PortX, dc: DC, wr: WR) -> Self {
-        Self { port, dc, wr }
+        Self::from_bus(WriteOnlyBus { port, dc, wr })
     }
 
     pub fn release(self) -> (PortX, DC, WR) {
-        (self.port, self.dc, self.wr)
+        let WriteOnlyBus { port, dc, wr } = self.bus;
+        (port, dc, wr)
     }
 }
 
-impl<PortX, DC, WR, Error> WriteOnlyInterface for GpioWriteOnly16BitInterface<PortX, DC, WR>
+impl<PortX, DC, WR, RD, Error> GpioReadWrite16BitInterface<PortX, DC, WR, RD, NoPin<Error>, NoPin<Error>, NoPin<Error>>
 where
-    PortX: WritePort,
+    PortX: ReadWritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
-    type Port = PortX;
-    type Error = Error;
-    type DC = DC;
-    type WR = WR;
-    fn write(&mut self) -> Result<Writer<PortX, DC, WR, Error>, Error> {
-        self.wr.set_high()?;
-        Ok(Writer {
-            port: &mut self.port,
-            dc: &mut self.dc,
-            wr: &mut self.wr,
+    pub fn new(port: PortX, dc: DC, wr: WR, rd: RD) -> Self {
+        Self::from_bus(ReadWriteBus { port, dc, wr, rd })
+    }
+}
+
+impl<Bus, RST, PWR, Error> Gpio16BitInterface<Bus, NoPin<Error>, RST, PWR> {
+    pub fn with_cs<CS>(self, mut cs: CS) -> Result<Gpio16BitInterface<Bus, CS, RST, PWR>, Error>
+    where
+        CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    {
+        cs.set_high()?;
+        let Self {
+            bus,
+            cs: _,
+            reset,
+            power,
+            reset_timing,
+        } = self;
+        Ok(Gpio16BitInterface {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
         })
     }
 }
 
-pub struct GpioReadWrite16BitInterface<Port, DC, WR, RD> {
-    port: Port,
-    dc: DC,
-    wr: WR,
-    rd: RD,
+impl<Bus, CS, PWR, Error> Gpio16BitInterface<Bus, CS, NoPin<Error>, PWR> {
+    // the controller is kept out of reset until `hard_reset` is called
+    pub fn with_reset<RST>(self, mut reset: RST) -> Result<Gpio16BitInterface<Bus, CS, RST, PWR>, Error>
+    where
+        RST: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    {
+        reset.set_high()?;
+        let Self {
+            bus,
+            cs,
+            reset: _,
+            power,
+            reset_timing,
+        } = self;
+        Ok(Gpio16BitInterface {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
+        })
+    }
+}
+
+impl<Bus, CS, RST, Error> Gpio16BitInterface<Bus, CS, RST, NoPin<Error>> {
+    // the power/backlight stays in the state it was handed over in
+    pub fn with_power<PWR>(self, power: PWR) -> Gpio16BitInterface<Bus, CS, RST, PWR>
+    where
+        PWR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    {
+        let Self {
+            bus,
+            cs,
+            reset,
+            power: _,
+            reset_timing,
+        } = self;
+        Gpio16BitInterface {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
+        }
+    }
 }
 
-impl<PortX, DC, WR, RD, Error> GpioReadWrite16BitInterface<PortX, DC, WR, RD>
+impl<Bus, CS, RST, PWR, Error> Gpio16BitInterface<Bus, CS, RST, PWR>
 where
-    PortX: ReadWritePort,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    RST: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    PWR: embedded_hal::digital::v2::OutputPin<Error = Error>,
+{
+    pub fn reset_timing(mut self, timing: ResetTiming) -> Self {
+        self.reset_timing = timing;
+        self
+    }
+
+    // pulses RESET low for `pulse_us` and waits `recovery_us` after releasing it
+    pub fn hard_reset<Delay: DelayUs<u32>>(&mut self, delay: &mut Delay) -> Result<(), Error> {
+        self.reset.set_high()?;
+        delay.delay_us(self.reset_timing.pulse_us);
+        self.reset.set_low()?;
+        delay.delay_us(self.reset_timing.pulse_us);
+        self.reset.set_high()?;
+        delay.delay_us(self.reset_timing.recovery_us);
+        Ok(())
+    }
+
+    pub fn power_on(&mut self) -> Result<(), Error> {
+        self.power.set_high()
+    }
+
+    pub fn power_off(&mut self) -> Result<(), Error> {
+        self.power.set_low()
+    }
+
+    pub fn release_cs(self) -> (Gpio16BitInterface<Bus, NoPin<Error>, RST, PWR>, CS) {
+        let Self {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
+        } = self;
+        let cs_free = Gpio16BitInterface {
+            bus,
+            cs: NoPin::new(),
+            reset,
+            power,
+            reset_timing,
+        };
+        (cs_free, cs)
+    }
+
+    pub fn release_reset(self) -> (Gpio16BitInterface<Bus, CS, NoPin<Error>, PWR>, RST) {
+        let Self {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
+        } = self;
+        let reset_free = Gpio16BitInterface {
+            bus,
+            cs,
+            reset: NoPin::new(),
+            power,
+            reset_timing,
+        };
+        (reset_free, reset)
+    }
+
+    pub fn release_power(self) -> (Gpio16BitInterface<Bus, CS, RST, NoPin<Error>>, PWR) {
+        let Self {
+            bus,
+            cs,
+            reset,
+            power,
+            reset_timing,
+        } = self;
+        let power_free = Gpio16BitInterface {
+            bus,
+            cs,
+            reset,
+            power: NoPin::new(),
+            reset_timing,
+        };
+        (power_free, power)
+    }
+}
+
+impl<PortX, DC, WR, CS, RST, PWR, Error> WriteOnlyInterface for GpioWriteOnly16BitInterface<PortX, DC, WR, CS, RST, PWR>
+where
+    PortX: WritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
-    RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
-    pub fn new(port: PortX, dc: DC, wr: WR, rd: RD) -> Self {
-        Self { port, dc, wr, rd }
+    type Port = PortX;
+    type Error = Error;
+    type DC = DC;
+    type WR = WR;
+    type CS = CS;
+    fn write(&mut self) -> Result<Writer<PortX, DC, WR, CS, Error>, Error> {
+        let bus = &mut self.bus;
+        bus.wr.set_high()?;
+        Writer::new(&mut bus.port, &mut bus.dc, &mut bus.wr, &mut self.cs)
     }
 }
 
-impl<PortX, DC, WR, RD, Error> WriteOnlyInterface for GpioReadWrite16BitInterface<PortX, DC, WR, RD>
+impl<PortX, DC, WR, RD, CS, RST, PWR, Error> WriteOnlyInterface for GpioReadWrite16BitInterface<PortX, DC, WR, RD, CS, RST, PWR>
 where
     PortX: ReadWritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     type Port = PortX;
     type Error = Error;
     type DC = DC;
     type WR = WR;
-    fn write(&mut self) -> Result<Writer<PortX, DC, WR, Error>, Error> {
-        self.wr.set_high()?;
-        self.rd.set_high()?;
-        self.port.dir_write();
-        Ok(Writer {
-            port: &mut self.port,
-            dc: &mut self.dc,
-            wr: &mut self.wr,
-        })
+    type CS = CS;
+    fn write(&mut self) -> Result<Writer<PortX, DC, WR, CS, Error>, Error> {
+        let bus = &mut self.bus;
+        bus.wr.set_high()?;
+        bus.rd.set_high()?;
+        bus.port.dir_write();
+        Writer::new(&mut bus.port, &mut bus.dc, &mut bus.wr, &mut self.cs)
     }
 }
 
-impl<PortX, DC, WR, RD, Error> ReadWriteInterface for GpioReadWrite16BitInterface<PortX, DC, WR, RD>
+impl<PortX, DC, WR, RD, CS, RST, PWR, Error> ReadWriteInterface for GpioReadWrite16BitInterface<PortX, DC, WR, RD, CS, RST, PWR>
 where
     PortX: ReadWritePort,
     DC: embedded_hal::digital::v2::OutputPin<Error = Error>,
     WR: embedded_hal::digital::v2::OutputPin<Error = Error>,
     RD: embedded_hal::digital::v2::OutputPin<Error = Error>,
+    CS: embedded_hal::digital::v2::OutputPin<Error = Error>,
 {
     type Port = PortX;
     type RD = RD;
-    fn read(&mut self) -> Result<Reader<PortX, DC, RD, Error>, Error> {
-        self.port.dir_read();
-        self.wr.set_high()?; // maybe not needed
-        self.rd.set_low()?; // read
-
-        Ok(Reader {
-            port: &mut self.port,
-            dc: &mut self.dc,
-            rd: &mut self.rd,
-        })
+    fn read(&mut self) -> Result<Reader<PortX, DC, RD, CS, Error>, Error> {
+        let bus = &mut self.bus;
+        bus.port.dir_read();
+        bus.wr.set_high()?; // maybe not needed
+
+        // the controller only sees RD while selected
+        let reader = Reader::new(&mut bus.port, &mut bus.dc, &mut bus.rd, &mut self.cs)?;
+        reader.rd.set_low()?; // read
+        Ok(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Delay, Event, Log};
+
+    #[test]
+    fn reset_is_released_when_added() {
+        let log = Log::default();
+        let _interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_reset(log.pin("reset"))
+            .unwrap();
+        assert_eq!(log.take(), [Event::Pin("reset", true)]);
+    }
+
+    #[test]
+    fn hard_reset_pulses_and_waits() {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_reset(log.pin("reset"))
+            .unwrap()
+            .reset_timing(ResetTiming {
+                pulse_us: 20,
+                recovery_us: 120_000,
+            });
+        log.take();
+        interface.hard_reset(&mut Delay(log.clone())).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Event::Pin("reset", true),
+                Event::Delay(20),
+                Event::Pin("reset", false),
+                Event::Delay(20),
+                Event::Pin("reset", true),
+                Event::Delay(120_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn hard_reset_without_pin_only_waits() {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"));
+        interface.hard_reset(&mut Delay(log.clone())).unwrap();
+        let timing = ResetTiming::default();
+        assert_eq!(
+            log.take(),
+            [
+                Event::Delay(timing.pulse_us),
+                Event::Delay(timing.pulse_us),
+                Event::Delay(timing.recovery_us)
+            ]
+        );
+    }
+
+    #[test]
+    fn power_is_left_alone_until_switched() {
+        let log = Log::default();
+        let mut interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr")).with_power(log.pin("power"));
+        assert!(log.take().is_empty());
+        interface.power_on().unwrap();
+        interface.power_off().unwrap();
+        assert_eq!(log.take(), [Event::Pin("power", true), Event::Pin("power", false)]);
+    }
+
+    #[test]
+    fn released_pins_are_no_longer_driven() {
+        let log = Log::default();
+        let interface = GpioWriteOnly16BitInterface::new(log.port(), log.pin("dc"), log.pin("wr"))
+            .with_reset(log.pin("reset"))
+            .unwrap()
+            .with_power(log.pin("power"));
+        let (interface, _reset) = interface.release_reset();
+        let (mut interface, _power) = interface.release_power();
+        log.take();
+        interface.power_on().unwrap();
+        interface.hard_reset(&mut Delay(log.clone())).unwrap();
+        assert!(log.take().iter().all(|event| matches!(event, Event::Delay(_))));
     }
 }
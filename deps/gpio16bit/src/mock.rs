@@ -0,0 +1,107 @@
+//! Pins and delays for the host tests that record what is done with them into a shared log.
+
+extern crate std;
+
+use core::cell::RefCell;
+use std::{rc::Rc, vec::Vec};
+
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::OutputPin};
+
+use crate::WritePort;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Pin(&'static str, bool),
+    Delay(u32),
+    Value(u16),
+    Abort,
+}
+
+#[derive(Clone, Default)]
+pub struct Log(Rc<RefCell<Vec<Event>>>);
+
+impl Log {
+    pub fn push(&self, event: Event) {
+        self.0.borrow_mut().push(event);
+    }
+
+    // everything recorded so far, which is forgotten
+    pub fn take(&self) -> Vec<Event> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+
+    pub fn pin(&self, name: &'static str) -> Pin {
+        Pin {
+            name,
+            log: self.clone(),
+            failing: false,
+        }
+    }
+
+    // a pin that can't be set, it fails without recording anything
+    pub fn failing_pin(&self, name: &'static str) -> Pin {
+        Pin {
+            name,
+            log: self.clone(),
+            failing: true,
+        }
+    }
+
+    pub fn port(&self) -> Port {
+        Port { log: self.clone() }
+    }
+}
+
+pub struct Port {
+    log: Log,
+}
+
+impl WritePort for Port {
+    fn set_value(&mut self, value: u16) {
+        self.log.push(Event::Value(value));
+    }
+}
+
+pub struct Pin {
+    name: &'static str,
+    log: Log,
+    failing: bool,
+}
+
+impl Pin {
+    fn set(&mut self, high: bool) -> Result<(), ()> {
+        if self.failing {
+            return Err(());
+        }
+        self.log.push(Event::Pin(self.name, high));
+        Ok(())
+    }
+}
+
+impl OutputPin for Pin {
+    type Error = ();
+    fn set_low(&mut self) -> Result<(), ()> {
+        self.set(false)
+    }
+    fn set_high(&mut self) -> Result<(), ()> {
+        self.set(true)
+    }
+}
+
+pub struct Delay(pub Log);
+
+impl DelayUs<u32> for Delay {
+    fn delay_us(&mut self, us: u32) {
+        self.0.push(Event::Delay(us));
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for Delay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.push(Event::Delay(ns / 1_000));
+    }
+    async fn delay_us(&mut self, us: u32) {
+        self.0.push(Event::Delay(us));
+    }
+}
@@ -0,0 +1,330 @@
+//! Ports built from 16 individual `embedded_hal` pins, for HALs without a dedicated port implementation.
+//!
+//! Every word takes 16 separate pin accesses, so these are a lot slower than a port written in a
+//! single store. `pins[0]` is D0. Pins of different types have to be erased to one type first,
+//! most HALs offer a dynamic or erased pin for that.
+//!
+//! The port traits can't fail, so the ports keep the first error of their pins for `error`.
+
+use embedded_hal::digital::v2::{InputPin, IoPin, OutputPin, PinState};
+
+use crate::{ReadWritePort, WritePort};
+
+// sets every pin, even after one of them failed, and returns the first error
+fn set_pins<P: OutputPin>(pins: &mut [P; 16], value: u16) -> Result<(), P::Error> {
+    let mut result = Ok(());
+    for (i, pin) in pins.iter_mut().enumerate() {
+        let set = pin.set_state(PinState::from(value & 1 << i != 0));
+        result = result.and(set);
+    }
+    result
+}
+
+fn get_pins<P: InputPin>(pins: &[P; 16]) -> Result<u16, P::Error> {
+    pins.iter()
+        .enumerate()
+        .try_fold(0, |value, (i, pin)| Ok(if pin.is_high()? { value | 1 << i } else { value }))
+}
+
+// the first error stays, later ones are dropped
+fn keep_first<E>(error: &mut Option<E>, result: Result<(), E>) {
+    if let Err(e) = result {
+        error.get_or_insert(e);
+    }
+}
+
+/// Write-only port from 16 push-pull outputs.
+pub struct OutputPinPort<P: OutputPin> {
+    pins: [P; 16],
+    error: Option<P::Error>,
+}
+
+impl<P: OutputPin> OutputPinPort<P> {
+    pub fn new(pins: [P; 16]) -> Self {
+        Self { pins, error: None }
+    }
+
+    // the first error of a pin, the port keeps setting the other pins after it
+    pub fn error(&self) -> Option<&P::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn release(self) -> [P; 16] {
+        self.pins
+    }
+}
+
+impl<P: OutputPin> WritePort for OutputPinPort<P> {
+    fn set_value(&mut self, value: u16) {
+        keep_first(&mut self.error, set_pins(&mut self.pins, value));
+    }
+}
+
+/// Read-write port from 16 open-drain pins with pull-ups, which are released (set high) for reading.
+pub struct OpenDrainPinPort<P: OutputPin + InputPin<Error = <P as OutputPin>::Error>> {
+    pins: [P; 16],
+    error: Option<<P as OutputPin>::Error>,
+}
+
+impl<P: OutputPin + InputPin<Error = <P as OutputPin>::Error>> OpenDrainPinPort<P> {
+    pub fn new(pins: [P; 16]) -> Self {
+        Self { pins, error: None }
+    }
+
+    // the first error of a pin, a value read after a failed read is 0
+    pub fn error(&self) -> Option<&<P as OutputPin>::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn release(self) -> [P; 16] {
+        self.pins
+    }
+}
+
+impl<P: OutputPin + InputPin<Error = <P as OutputPin>::Error>> WritePort for OpenDrainPinPort<P> {
+    fn set_value(&mut self, value: u16) {
+        keep_first(&mut self.error, set_pins(&mut self.pins, value));
+    }
+}
+
+impl<P: OutputPin + InputPin<Error = <P as OutputPin>::Error>> ReadWritePort for OpenDrainPinPort<P> {
+    fn get_value(&mut self) -> u16 {
+        get_pins(&self.pins).unwrap_or_else(|e| {
+            self.error.get_or_insert(e);
+            0
+        })
+    }
+    fn dir_read(&mut self) {
+        keep_first(&mut self.error, set_pins(&mut self.pins, 0xffff));
+    }
+    fn dir_write(&mut self) {}
+}
+
+enum Pins<I, O> {
+    Read([I; 16]),
+    Write([O; 16]),
+}
+
+// a pin whose conversion fails is gone, so are the ones converted before it
+fn convert<A, B, Error>(pins: [A; 16], mut convert: impl FnMut(A) -> Result<B, Error>) -> Result<[B; 16], Error> {
+    let mut error = None;
+    let pins = pins.map(|pin| convert(pin).map_err(|e| error = Some(e)).ok());
+    match error {
+        Some(error) => Err(error),
+        None => Ok(pins.map(Option::unwrap)),
+    }
+}
+
+/// Read-write port from 16 pins that switch between input and output types through `IoPin`.
+///
+/// After a failed switch the port no longer drives or reads the bus, `release` returns the first
+/// error then.
+pub struct IoPinPort<I, O, Error> {
+    // `None` after a failed switch
+    pins: Option<Pins<I, O>>,
+    error: Option<Error>,
+}
+
+impl<I, O, Error> IoPinPort<I, O, Error>
+where
+    I: InputPin<Error = Error> + IoPin<I, O, Error = Error>,
+    O: OutputPin<Error = Error> + IoPin<I, O, Error = Error>,
+{
+    // accepts the pins as inputs, the bus starts out in write direction like the other ports
+    pub fn new(pins: [I; 16]) -> Result<Self, Error> {
+        let pins = convert(pins, |pin| pin.into_output_pin(PinState::High))?;
+        Ok(Self {
+            pins: Some(Pins::Write(pins)),
+            error: None,
+        })
+    }
+
+    pub fn release(mut self) -> Result<[I; 16], Error> {
+        self.switch_to_read();
+        match (self.pins, self.error) {
+            (Some(Pins::Read(pins)), _) => Ok(pins),
+            (_, Some(error)) => Err(error),
+            _ => unreachable!(),
+        }
+    }
+
+    // the first error of a pin or a direction switch, the port stays unusable after a failed switch
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    fn switch_to_read(&mut self) {
+        self.pins = match self.pins.take() {
+            Some(Pins::Write(pins)) => match convert(pins, |pin| pin.into_input_pin()) {
+                Ok(pins) => Some(Pins::Read(pins)),
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                    None
+                }
+            },
+            pins => pins,
+        };
+    }
+
+    fn switch_to_write(&mut self) {
+        self.pins = match self.pins.take() {
+            Some(Pins::Read(pins)) => match convert(pins, |pin| pin.into_output_pin(PinState::High)) {
+                Ok(pins) => Some(Pins::Write(pins)),
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                    None
+                }
+            },
+            pins => pins,
+        };
+    }
+}
+
+// both directions are switched on demand, so a write-only interface that never calls `dir_write`
+// still gets the pins driven
+impl<I, O, Error> WritePort for IoPinPort<I, O, Error>
+where
+    I: InputPin<Error = Error> + IoPin<I, O, Error = Error>,
+    O: OutputPin<Error = Error> + IoPin<I, O, Error = Error>,
+{
+    fn set_value(&mut self, value: u16) {
+        self.switch_to_write();
+        if let Some(Pins::Write(pins)) = &mut self.pins {
+            keep_first(&mut self.error, set_pins(pins, value));
+        }
+    }
+}
+
+impl<I, O, Error> ReadWritePort for IoPinPort<I, O, Error>
+where
+    I: InputPin<Error = Error> + IoPin<I, O, Error = Error>,
+    O: OutputPin<Error = Error> + IoPin<I, O, Error = Error>,
+{
+    fn get_value(&mut self) -> u16 {
+        self.switch_to_read();
+        let value = match &self.pins {
+            Some(Pins::Read(pins)) => get_pins(pins),
+            // only after a failed switch, see `error`
+            _ => Ok(0),
+        };
+        value.unwrap_or_else(|e| {
+            self.error.get_or_insert(e);
+            0
+        })
+    }
+    fn dir_read(&mut self) {
+        self.switch_to_read();
+    }
+    fn dir_write(&mut self) {
+        self.switch_to_write();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // one line of a shared 16 bit bus
+    struct Pin {
+        bit: u16,
+        bus: Rc<Cell<u16>>,
+        fails_to_input: bool,
+        fails_to_set: bool,
+    }
+
+    impl InputPin for Pin {
+        type Error = ();
+        fn is_high(&self) -> Result<bool, ()> {
+            Ok(self.bus.get() & self.bit != 0)
+        }
+        fn is_low(&self) -> Result<bool, ()> {
+            Ok(self.bus.get() & self.bit == 0)
+        }
+    }
+
+    impl OutputPin for Pin {
+        type Error = ();
+        fn set_low(&mut self) -> Result<(), ()> {
+            if self.fails_to_set {
+                return Err(());
+            }
+            self.bus.set(self.bus.get() & !self.bit);
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), ()> {
+            if self.fails_to_set {
+                return Err(());
+            }
+            self.bus.set(self.bus.get() | self.bit);
+            Ok(())
+        }
+    }
+
+    impl IoPin<Pin, Pin> for Pin {
+        type Error = ();
+        fn into_input_pin(self) -> Result<Pin, ()> {
+            if self.fails_to_input {
+                return Err(());
+            }
+            Ok(self)
+        }
+        fn into_output_pin(mut self, state: PinState) -> Result<Pin, ()> {
+            self.set_state(state).ok();
+            Ok(self)
+        }
+    }
+
+    fn pins(bus: &Rc<Cell<u16>>, failing: Option<usize>) -> [Pin; 16] {
+        core::array::from_fn(|i| Pin {
+            bit: 1 << i,
+            bus: bus.clone(),
+            fails_to_input: failing == Some(i),
+            fails_to_set: false,
+        })
+    }
+
+    #[test]
+    fn writes_without_dir_write() {
+        let bus = Rc::new(Cell::new(0));
+        let mut port = IoPinPort::new(pins(&bus, None)).unwrap();
+        port.set_value(0xa55a);
+        assert_eq!(bus.get(), 0xa55a);
+
+        bus.set(0x1234);
+        assert_eq!(port.get_value(), 0x1234);
+        port.set_value(0x00ff);
+        assert_eq!(bus.get(), 0x00ff);
+        assert!(port.release().is_ok());
+    }
+
+    #[test]
+    fn failed_switch_is_reported() {
+        let bus = Rc::new(Cell::new(0));
+        let mut port = IoPinPort::new(pins(&bus, Some(3))).unwrap();
+        port.dir_read();
+        assert_eq!(port.error(), Some(&()));
+        bus.set(0);
+        port.set_value(0xffff);
+        assert_eq!(bus.get(), 0);
+
+        let mut port = IoPinPort::new(pins(&bus, Some(3))).unwrap();
+        port.get_value();
+        assert_eq!(port.release().err(), Some(()));
+    }
+
+    #[test]
+    fn first_pin_error_is_kept() {
+        let bus = Rc::new(Cell::new(0));
+        let mut pins = pins(&bus, None);
+        pins[5].fails_to_set = true;
+        let mut port = OutputPinPort::new(pins);
+        port.set_value(0xffff);
+        assert_eq!(bus.get(), !(1 << 5));
+        assert_eq!(port.error(), Some(&()));
+    }
+}
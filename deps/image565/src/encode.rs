@@ -0,0 +1,224 @@
+use std::convert::TryFrom;
+
+use crate::{qoi_hash, Encoding, HEADER_LEN, MAGIC};
+
+/// RGB565 of an 8 bit per channel color.
+pub fn rgb565(r: u8, g: u8, b: u8) -> u16 {
+    crate::rgb565(r, g, b)
+}
+
+// back to 8 bits per channel, repeating the high bits in the low ones so white stays white
+fn rgb888(pixel: u16) -> [u8; 4] {
+    let (r, g, b) = ((pixel >> 11) as u8, (pixel >> 5 & 0x3f) as u8, (pixel & 0x1f) as u8);
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]
+}
+
+/// RGB565 of RGBA pixels blended over `background`.
+pub fn from_rgba8(rgba: &[u8], background: [u8; 3]) -> Vec<u16> {
+    rgba.chunks_exact(4)
+        .map(|pixel| {
+            let alpha = u16::from(pixel[3]);
+            let blend = |channel: usize| ((u16::from(pixel[channel]) * alpha + u16::from(background[channel]) * (255 - alpha) + 127) / 255) as u8;
+            rgb565(blend(0), blend(1), blend(2))
+        })
+        .collect()
+}
+
+fn raw(pixels: &[u16], out: &mut Vec<u8>) {
+    for pixel in pixels {
+        out.extend_from_slice(&pixel.to_le_bytes());
+    }
+}
+
+fn rle(pixels: &[u16], out: &mut Vec<u8>) {
+    let mut i = 0;
+    let mut literal_start = 0;
+    let flush_literal = |out: &mut Vec<u8>, literal: &[u16]| {
+        for chunk in literal.chunks(128) {
+            out.push(u8::try_from(chunk.len() - 1).unwrap());
+            raw(chunk, out);
+        }
+    };
+    while i < pixels.len() {
+        let run = pixels[i..].iter().take(128).take_while(|&&pixel| pixel == pixels[i]).count();
+        // a run of two costs as much as two literal pixels, and breaks up the literal packet
+        if run > 2 {
+            flush_literal(out, &pixels[literal_start..i]);
+            out.push(u8::try_from(run + 0x7f).unwrap());
+            raw(&pixels[i..=i], out);
+            i += run;
+            literal_start = i;
+        } else {
+            i += run;
+        }
+    }
+    flush_literal(out, &pixels[literal_start..]);
+}
+
+fn qoi(pixels: &[u16], out: &mut Vec<u8>) {
+    let mut index = [[0u8; 4]; 64];
+    let mut previous = [0, 0, 0, 255];
+    let mut run = 0u8;
+    for &pixel in pixels {
+        let pixel = rgb888(pixel);
+        if pixel == previous {
+            run += 1;
+            if run == 62 {
+                out.push(0xc0 | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            out.push(0xc0 | (run - 1));
+            run = 0;
+        }
+        let hash = qoi_hash(pixel);
+        if index[hash] == pixel {
+            out.push(u8::try_from(hash).unwrap());
+        } else {
+            index[hash] = pixel;
+            let dr = pixel[0].wrapping_sub(previous[0]) as i8;
+            let dg = pixel[1].wrapping_sub(previous[1]) as i8;
+            let db = pixel[2].wrapping_sub(previous[2]) as i8;
+            let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+            if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                out.push(0x40 | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+            } else if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg) {
+                out.push(0x80 | (dg + 32) as u8);
+                out.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+            } else {
+                out.extend_from_slice(&[0xfe, pixel[0], pixel[1], pixel[2]]);
+            }
+        }
+        previous = pixel;
+    }
+    if run > 0 {
+        out.push(0xc0 | (run - 1));
+    }
+}
+
+/// Image of `pixels`, row by row.
+///
+/// Panics if there aren't `width * height` pixels.
+pub fn encode(width: u16, height: u16, pixels: &[u16], encoding: Encoding) -> Vec<u8> {
+    assert_eq!(pixels.len(), usize::from(width) * usize::from(height));
+    let mut out = Vec::with_capacity(HEADER_LEN + 2 * pixels.len());
+    out.extend_from_slice(MAGIC);
+    out.push(encoding as u8);
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    match encoding {
+        Encoding::Raw => raw(pixels, &mut out),
+        Encoding::Rle => rle(pixels, &mut out),
+        Encoding::Qoi => qoi(pixels, &mut out),
+    }
+    out
+}
+
+/// The smallest of the encodings, raw on a tie since it is the fastest to draw.
+pub fn encode_smallest(width: u16, height: u16, pixels: &[u16]) -> Vec<u8> {
+    [Encoding::Raw, Encoding::Rle, Encoding::Qoi]
+        .iter()
+        .map(|&encoding| encode(width, height, pixels, encoding))
+        .min_by_key(|image| image.len())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Image};
+
+    const ENCODINGS: [Encoding; 3] = [Encoding::Raw, Encoding::Rle, Encoding::Qoi];
+
+    fn decode(image: &[u8]) -> Vec<u16> {
+        Image::new(image).unwrap().pixels().collect()
+    }
+
+    // a single row, in every encoding; returns the data after the header of each
+    fn round_trip(pixels: &[u16]) -> Vec<Vec<u8>> {
+        let width = u16::try_from(pixels.len()).unwrap();
+        ENCODINGS
+            .iter()
+            .map(|&encoding| {
+                let image = encode(width, 1, pixels, encoding);
+                assert_eq!(decode(&image), pixels, "{:?}", encoding);
+                image[HEADER_LEN..].to_vec()
+            })
+            .collect()
+    }
+
+    // no two neighbours alike
+    fn distinct(count: usize) -> Vec<u16> {
+        (0..count).map(|i| u16::try_from(i * 0x1234 % 0xffff).unwrap()).collect()
+    }
+
+    #[test]
+    fn mixed_image() {
+        let mut pixels = distinct(40);
+        pixels.extend(vec![0x1234; 200]);
+        pixels.extend((0..60).map(|i| rgb565(i * 4, 128, 255 - i * 4)));
+        pixels.extend(vec![0; 100]);
+        for &encoding in ENCODINGS.iter() {
+            let image = encode(20, 20, &pixels, encoding);
+            assert_eq!(Image::new(&image).unwrap().encoding(), encoding);
+            assert_eq!(decode(&image), pixels, "{:?}", encoding);
+        }
+        assert_eq!(decode(&encode_smallest(20, 20, &pixels)), pixels);
+    }
+
+    #[test]
+    fn rle_packet_boundaries() {
+        for &(len, packets) in [(127, 1), (128, 1), (129, 2)].iter() {
+            // repeated, the pixel left over after 128 goes in a literal packet
+            let repeated = vec![0xabcd; len];
+            assert_eq!(round_trip(&repeated)[1].len(), 3 * packets, "{}", len);
+            let literal = distinct(len);
+            assert_eq!(round_trip(&literal)[1].len(), packets + 2 * len, "{}", len);
+        }
+    }
+
+    #[test]
+    fn qoi_runs() {
+        // black is the pixel before the first one, so these are runs from the start
+        for &(len, ref expected) in [
+            (1, vec![0xc0]),
+            (61, vec![0xfc]),
+            (62, vec![0xfd]),
+            (63, vec![0xfd, 0xc0]),
+            (124, vec![0xfd, 0xfd]),
+        ]
+        .iter()
+        {
+            assert_eq!(&round_trip(&vec![0; len])[2], expected, "{}", len);
+        }
+        // a color of its own first, then runs of 62, 62 and 5
+        assert_eq!(round_trip(&[0x8410; 130])[2], [0xfe, 132, 130, 132, 0xfd, 0xfd, 0xc4]);
+    }
+
+    #[test]
+    fn qoi_index_hits() {
+        let (a, b) = (rgb565(200, 40, 90), rgb565(10, 220, 160));
+        let data = &round_trip(&[a, b, a, b])[2];
+        let hash = |pixel| u8::try_from(qoi_hash(rgb888(pixel))).unwrap();
+        assert_eq!(&data[data.len() - 2..], &[hash(a), hash(b)]);
+    }
+
+    #[test]
+    fn truncated_data() {
+        let mut pixels = distinct(50);
+        pixels.extend(vec![0x4321; 300]);
+        for &encoding in ENCODINGS.iter().skip(1) {
+            let image = encode(350, 1, &pixels, encoding);
+            for cut in 1..image.len() - HEADER_LEN {
+                let decoded = decode(&image[..image.len() - cut]);
+                assert!(decoded.len() < pixels.len(), "{:?} {}", encoding, cut);
+                assert_eq!(decoded, pixels[..decoded.len()], "{:?} {}", encoding, cut);
+            }
+        }
+        let image = encode(350, 1, &pixels, Encoding::Raw);
+        assert_eq!(Image::new(&image[..image.len() - 1]).err(), Some(Error::Length));
+        assert_eq!(Image::new(&image[..HEADER_LEN - 1]).err(), Some(Error::Header));
+    }
+}
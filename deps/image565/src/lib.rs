@@ -0,0 +1,265 @@
+//! RGB565 images to keep in flash and draw without a framebuffer.
+//!
+//! An image is an 8 byte header followed by the pixels, row by row:
+//!
+//! - `b"565"`
+//! - the encoding, 0 for raw, 1 for RLE and 2 for QOI
+//! - width and height, both little endian `u16`
+//!
+//! Raw pixels are little endian `u16`. RLE packets start with a byte `n`, below 0x80 it is followed by
+//! `n + 1` raw pixels, otherwise by a single pixel repeated `n - 0x7f` times. QOI is the chunk stream of
+//! the QOI format without its header and end marker, with the colors quantized to RGB565 before encoding.
+//!
+//! `Pixels` decodes while it is iterated, which is what `fill_area` wants. The `encode` feature adds the
+//! encoder for build scripts.
+
+#![cfg_attr(not(feature = "encode"), no_std)]
+
+#[cfg(feature = "encode")]
+pub mod encode;
+
+use core::convert::TryFrom;
+
+pub const MAGIC: &[u8; 3] = b"565";
+pub const HEADER_LEN: usize = 8;
+
+/// Image written to `OUT_DIR` by a build script, as `&'static [u8]`.
+#[macro_export]
+macro_rules! include_image {
+    ($name:literal) => {
+        include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".img"))
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Raw = 0,
+    Rle = 1,
+    Qoi = 2,
+}
+
+impl TryFrom<u8> for Encoding {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Error> {
+        match value {
+            0 => Ok(Encoding::Raw),
+            1 => Ok(Encoding::Rle),
+            2 => Ok(Encoding::Qoi),
+            _ => Err(Error::Encoding),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// not an image or the header is cut short
+    Header,
+    Encoding,
+    /// raw pixels don't match the size
+    Length,
+}
+
+#[derive(Clone, Copy)]
+pub struct Image<'a> {
+    encoding: Encoding,
+    width: u16,
+    height: u16,
+    data: &'a [u8],
+}
+
+impl<'a> Image<'a> {
+    // compressed data is only checked while decoding, `Pixels` ends early on a truncated stream
+    pub fn new(image: &'a [u8]) -> Result<Self, Error> {
+        if image.len() < HEADER_LEN || &image[..3] != MAGIC {
+            return Err(Error::Header);
+        }
+        let encoding = Encoding::try_from(image[3])?;
+        let width = u16::from_le_bytes([image[4], image[5]]);
+        let height = u16::from_le_bytes([image[6], image[7]]);
+        let data = &image[HEADER_LEN..];
+        if encoding == Encoding::Raw && data.len() != 2 * usize::from(width) * usize::from(height) {
+            return Err(Error::Length);
+        }
+        Ok(Self {
+            encoding,
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn pixels(&self) -> Pixels<'a> {
+        let state = match self.encoding {
+            Encoding::Raw => State::Raw,
+            Encoding::Rle => State::Rle(Rle { left: 0, repeat: None }),
+            Encoding::Qoi => State::Qoi(Qoi::new()),
+        };
+        Pixels {
+            data: self.data,
+            remaining: u32::from(self.width) * u32::from(self.height),
+            state,
+        }
+    }
+}
+
+fn rgb565(r: u8, g: u8, b: u8) -> u16 {
+    u16::from(r >> 3) << 11 | u16::from(g >> 2) << 5 | u16::from(b >> 3)
+}
+
+fn qoi_hash(pixel: [u8; 4]) -> usize {
+    (usize::from(pixel[0]) * 3 + usize::from(pixel[1]) * 5 + usize::from(pixel[2]) * 7 + usize::from(pixel[3]) * 11) % 64
+}
+
+struct Qoi {
+    index: [[u8; 4]; 64],
+    previous: [u8; 4],
+    run: u8,
+}
+
+impl Qoi {
+    fn new() -> Self {
+        Self {
+            index: [[0; 4]; 64],
+            previous: [0, 0, 0, 255],
+            run: 0,
+        }
+    }
+
+    fn next(&mut self, data: &mut &[u8]) -> Option<[u8; 4]> {
+        if self.run > 0 {
+            self.run -= 1;
+            return Some(self.previous);
+        }
+        let (&tag, rest) = data.split_first()?;
+        *data = rest;
+        let mut take = |count: usize| -> Option<&[u8]> {
+            if data.len() < count {
+                return None;
+            }
+            let (taken, rest) = data.split_at(count);
+            *data = rest;
+            Some(taken)
+        };
+        let [r, g, b, a] = self.previous;
+        let pixel = match tag {
+            0xfe => {
+                let rgb = take(3)?;
+                [rgb[0], rgb[1], rgb[2], a]
+            }
+            0xff => {
+                let rgba = take(4)?;
+                [rgba[0], rgba[1], rgba[2], rgba[3]]
+            }
+            _ => match tag >> 6 {
+                0 => self.index[usize::from(tag)],
+                1 => [
+                    r.wrapping_add((tag >> 4 & 3).wrapping_sub(2)),
+                    g.wrapping_add((tag >> 2 & 3).wrapping_sub(2)),
+                    b.wrapping_add((tag & 3).wrapping_sub(2)),
+                    a,
+                ],
+                2 => {
+                    let dg = (tag & 0x3f).wrapping_sub(32);
+                    let second = take(1)?[0];
+                    [
+                        r.wrapping_add(dg.wrapping_sub(8).wrapping_add(second >> 4)),
+                        g.wrapping_add(dg),
+                        b.wrapping_add(dg.wrapping_sub(8).wrapping_add(second & 0xf)),
+                        a,
+                    ]
+                }
+                _ => {
+                    self.run = tag & 0x3f;
+                    self.previous
+                }
+            },
+        };
+        self.index[qoi_hash(pixel)] = pixel;
+        self.previous = pixel;
+        Some(pixel)
+    }
+}
+
+fn raw(data: &mut &[u8]) -> Option<u16> {
+    if data.len() < 2 {
+        return None;
+    }
+    let (pixel, rest) = data.split_at(2);
+    *data = rest;
+    Some(u16::from_le_bytes([pixel[0], pixel[1]]))
+}
+
+struct Rle {
+    // pixels left in the current packet, with the pixel for a repeat packet
+    left: u8,
+    repeat: Option<u16>,
+}
+
+impl Rle {
+    fn next(&mut self, data: &mut &[u8]) -> Option<u16> {
+        if self.left == 0 {
+            let (&packet, rest) = data.split_first()?;
+            *data = rest;
+            if packet < 0x80 {
+                self.left = packet + 1;
+                self.repeat = None;
+            } else {
+                self.left = packet - 0x7f;
+                self.repeat = Some(raw(data)?);
+            }
+        }
+        self.left -= 1;
+        match self.repeat {
+            Some(pixel) => Some(pixel),
+            None => raw(data),
+        }
+    }
+}
+
+// no allocator to box the QOI index in, and a QOI image needs it anyway
+#[allow(clippy::large_enum_variant)]
+enum State {
+    Raw,
+    Rle(Rle),
+    Qoi(Qoi),
+}
+
+pub struct Pixels<'a> {
+    data: &'a [u8],
+    remaining: u32,
+    state: State,
+}
+
+impl<'a> Iterator for Pixels<'a> {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let pixel = match &mut self.state {
+            State::Raw => raw(&mut self.data),
+            State::Rle(rle) => rle.next(&mut self.data),
+            State::Qoi(qoi) => qoi.next(&mut self.data).map(|[r, g, b, _]| rgb565(r, g, b)),
+        };
+        if pixel.is_some() {
+            self.remaining -= 1;
+        }
+        pixel
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(usize::try_from(self.remaining).unwrap()))
+    }
+}
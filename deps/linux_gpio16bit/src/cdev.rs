@@ -0,0 +1,65 @@
+use gpio_cdev::{Chip, Error, LineRequestFlags, MultiLineHandle};
+
+use crate::{LinePin, LinePort, Lines};
+
+pub struct CdevLines {
+    lines: gpio_cdev::Lines,
+    // only `None` while switching direction, the kernel refuses to request busy lines
+    handle: Option<MultiLineHandle>,
+    consumer: String,
+}
+
+impl CdevLines {
+    // requests `offsets` of `chip` as outputs starting at `values`
+    pub fn new(chip: &mut Chip, offsets: &[u32], values: &[u8], consumer: &str) -> Result<Self, Error> {
+        let lines = chip.get_lines(offsets)?;
+        let handle = lines.request(LineRequestFlags::OUTPUT, values, consumer)?;
+        Ok(Self {
+            lines,
+            handle: Some(handle),
+            consumer: consumer.to_owned(),
+        })
+    }
+
+    fn request(&mut self, flags: LineRequestFlags, values: &[u8]) -> Result<(), Error> {
+        self.handle = None;
+        self.handle = Some(self.lines.request(flags, values, &self.consumer)?);
+        Ok(())
+    }
+
+    fn handle(&self) -> &MultiLineHandle {
+        self.handle.as_ref().unwrap()
+    }
+
+    // frees the lines
+    pub fn release(self) -> gpio_cdev::Lines {
+        self.lines
+    }
+}
+
+impl Lines for CdevLines {
+    type Error = Error;
+    fn set_values(&mut self, values: &[u8]) -> Result<(), Error> {
+        self.handle().set_values(values)
+    }
+    fn get_values(&mut self, values: &mut [u8]) -> Result<(), Error> {
+        values.copy_from_slice(&self.handle().get_values()?);
+        Ok(())
+    }
+    fn set_output(&mut self, values: &[u8]) -> Result<(), Error> {
+        self.request(LineRequestFlags::OUTPUT, values)
+    }
+    fn set_input(&mut self) -> Result<(), Error> {
+        self.request(LineRequestFlags::INPUT, &vec![0; self.lines.len()])
+    }
+}
+
+/// Data port on the lines at `offsets` of `chip`, D0 first.
+pub fn port(chip: &mut Chip, offsets: [u32; 16], consumer: &str) -> Result<LinePort<CdevLines>, Error> {
+    Ok(LinePort::new(CdevLines::new(chip, &offsets, &[0; 16], consumer)?))
+}
+
+// starts high, the idle level of the 8080 strobes
+pub fn pin(chip: &mut Chip, offset: u32, consumer: &str) -> Result<LinePin<CdevLines>, Error> {
+    Ok(LinePin::new(CdevLines::new(chip, &[offset], &[1], consumer)?))
+}
@@ -0,0 +1,259 @@
+//! In-process GPIO chip with an 8080 bus device attached, to run the bus without hardware.
+//!
+//! The device logs every word strobed in by a rising WR edge and presents queued words on the data
+//! lines while RD is low, moving on to the next one on the rising RD edge. Both only while CS is
+//! low, if there is a CS line.
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use crate::{LinePin, LinePort, Lines};
+
+/// Word the device saw on a rising WR edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Command(u16),
+    Data(u16),
+}
+
+/// Offsets of the bus signals on the fake chip.
+#[derive(Clone, Copy, Debug)]
+pub struct BusLines {
+    /// D0 first
+    pub data: [u32; 16],
+    pub dc: u32,
+    pub wr: u32,
+    pub rd: u32,
+    pub cs: Option<u32>,
+}
+
+struct State {
+    values: Vec<u8>,
+    bus: BusLines,
+    writes: Vec<Access>,
+    reads: VecDeque<u16>,
+    // levels at the previous update, to find the edges
+    wr: u8,
+    rd: u8,
+}
+
+impl State {
+    fn level(&self, offset: u32) -> u8 {
+        self.values[offset as usize]
+    }
+
+    fn data(&self) -> u16 {
+        self.bus
+            .data
+            .iter()
+            .enumerate()
+            .fold(0, |value, (i, &line)| value | u16::from(self.level(line)) << i)
+    }
+
+    fn drive(&mut self, value: u16) {
+        for (i, &line) in self.bus.data.iter().enumerate() {
+            self.values[line as usize] = u8::from(value & 1 << i != 0);
+        }
+    }
+
+    fn update(&mut self) {
+        let selected = self.bus.cs.is_none_or(|cs| self.level(cs) == 0);
+        let (wr, rd) = (self.level(self.bus.wr), self.level(self.bus.rd));
+        if selected && self.wr == 0 && wr == 1 {
+            let value = self.data();
+            self.writes.push(if self.level(self.bus.dc) == 0 {
+                Access::Command(value)
+            } else {
+                Access::Data(value)
+            });
+        }
+        if selected && self.rd == 0 && rd == 1 {
+            self.reads.pop_front();
+        }
+        self.wr = wr;
+        self.rd = rd;
+        if rd == 0 {
+            if let Some(&value) = self.reads.front() {
+                self.drive(value);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FakeChip {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeChip {
+    // `num_lines` lines, WR, RD and CS start high and the rest low
+    pub fn new(num_lines: u32, bus: BusLines) -> Self {
+        let mut values = vec![0; num_lines as usize];
+        for &line in bus.data.iter().chain(&[bus.dc, bus.wr, bus.rd]).chain(&bus.cs) {
+            assert!(line < num_lines);
+        }
+        for &line in [bus.wr, bus.rd].iter().chain(&bus.cs) {
+            values[line as usize] = 1;
+        }
+        let state = State {
+            values,
+            bus,
+            writes: Vec::new(),
+            reads: VecDeque::new(),
+            wr: 1,
+            rd: 1,
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    // panics if an offset is out of range
+    pub fn lines(&self, offsets: &[u32]) -> FakeLines {
+        let num_lines = self.state.lock().unwrap().values.len();
+        assert!(offsets.iter().all(|&offset| (offset as usize) < num_lines));
+        FakeLines {
+            chip: self.clone(),
+            offsets: offsets.to_vec(),
+            output: true,
+        }
+    }
+
+    pub fn port(&self) -> LinePort<FakeLines> {
+        let data = self.state.lock().unwrap().bus.data;
+        LinePort::new(self.lines(&data))
+    }
+
+    pub fn pin(&self, offset: u32) -> LinePin<FakeLines> {
+        LinePin::new(self.lines(&[offset]))
+    }
+
+    pub fn level(&self, offset: u32) -> u8 {
+        self.state.lock().unwrap().level(offset)
+    }
+
+    // everything written since the last call
+    pub fn take_writes(&self) -> Vec<Access> {
+        std::mem::take(&mut self.state.lock().unwrap().writes)
+    }
+
+    // words for the device to answer reads with, in order
+    pub fn queue_reads(&self, values: &[u16]) {
+        let mut state = self.state.lock().unwrap();
+        state.reads.extend(values);
+        state.update();
+    }
+}
+
+pub struct FakeLines {
+    chip: FakeChip,
+    offsets: Vec<u32>,
+    output: bool,
+}
+
+impl Lines for FakeLines {
+    type Error = Infallible;
+    // like the character device, but ignored instead of failing on inputs
+    fn set_values(&mut self, values: &[u8]) -> Result<(), Infallible> {
+        if self.output {
+            let mut state = self.chip.state.lock().unwrap();
+            for (&offset, &value) in self.offsets.iter().zip(values) {
+                state.values[offset as usize] = u8::from(value != 0);
+            }
+            state.update();
+        }
+        Ok(())
+    }
+    fn get_values(&mut self, values: &mut [u8]) -> Result<(), Infallible> {
+        let state = self.chip.state.lock().unwrap();
+        for (&offset, value) in self.offsets.iter().zip(values) {
+            *value = state.level(offset);
+        }
+        Ok(())
+    }
+    fn set_output(&mut self, values: &[u8]) -> Result<(), Infallible> {
+        self.output = true;
+        self.set_values(values)
+    }
+    fn set_input(&mut self) -> Result<(), Infallible> {
+        self.output = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpio16bit::{GpioReadWrite16BitInterface, ReadWriteInterface, WriteOnlyInterface};
+
+    use super::*;
+
+    const CS: u32 = 19;
+    const BUS: BusLines = BusLines {
+        data: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        dc: 16,
+        wr: 17,
+        rd: 18,
+        cs: Some(CS),
+    };
+
+    type Interface = GpioReadWrite16BitInterface<
+        LinePort<FakeLines>,
+        LinePin<FakeLines>,
+        LinePin<FakeLines>,
+        LinePin<FakeLines>,
+        LinePin<FakeLines>,
+        gpio16bit::NoPin<Infallible>,
+        gpio16bit::NoPin<Infallible>,
+    >;
+
+    fn interface(chip: &FakeChip) -> Interface {
+        GpioReadWrite16BitInterface::new(chip.port(), chip.pin(BUS.dc), chip.pin(BUS.wr), chip.pin(BUS.rd))
+            .with_cs(chip.pin(CS))
+            .unwrap()
+    }
+
+    // WR, RD and CS levels
+    fn strobes(chip: &FakeChip) -> [u8; 3] {
+        [chip.level(BUS.wr), chip.level(BUS.rd), chip.level(CS)]
+    }
+
+    #[test]
+    fn write_is_strobed_in() {
+        let chip = FakeChip::new(20, BUS);
+        let mut interface = interface(&chip);
+        {
+            let mut writer = interface.write().unwrap();
+            writer.command().unwrap().set_value(0x2c).unwrap().commit().unwrap();
+            let mut setter = writer.data().unwrap();
+            setter.set_value(0x1234).unwrap().commit().unwrap();
+            setter.set_value(0xf800).unwrap().commit().unwrap();
+        }
+        assert_eq!(chip.take_writes(), [Access::Command(0x2c), Access::Data(0x1234), Access::Data(0xf800)]);
+        assert_eq!(chip.level(BUS.dc), 1);
+        assert_eq!(strobes(&chip), [1, 1, 1]);
+    }
+
+    #[test]
+    fn read_takes_one_queued_word_per_rd_pulse() {
+        let chip = FakeChip::new(20, BUS);
+        let mut interface = interface(&chip);
+        chip.queue_reads(&[0xbeef, 0x0001]);
+        {
+            let mut reader = interface.read().unwrap();
+            let mut getter = reader.data().unwrap();
+            assert_eq!(getter.get_value().unwrap(), 0xbeef);
+            assert_eq!(getter.get_value().unwrap(), 0x0001);
+        }
+        assert!(chip.take_writes().is_empty());
+        assert_eq!(chip.level(BUS.dc), 1);
+        // deselected, RD is only raised by the next write, before it drives the data lines
+        assert_eq!(strobes(&chip), [1, 0, 1]);
+
+        interface.write().unwrap().data().unwrap().set_value(0x00ff).unwrap().commit().unwrap();
+        assert_eq!(chip.take_writes(), [Access::Data(0x00ff)]);
+        assert_eq!(strobes(&chip), [1, 1, 1]);
+    }
+}
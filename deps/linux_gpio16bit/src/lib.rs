@@ -0,0 +1,104 @@
+//! `gpio16bit` port and pins on top of the Linux GPIO character device, for bring-up from an SBC.
+//!
+//! The 16 data lines are a single line request, so a word is set or read with one ioctl. `FakeChip`
+//! stands in for the character device to run the bus on machines without GPIOs.
+//!
+//! Both need the `std` feature, without it the crate is `no_std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod cdev;
+#[cfg(feature = "std")]
+pub mod fake;
+
+#[cfg(feature = "std")]
+pub use cdev::CdevLines;
+#[cfg(feature = "std")]
+pub use fake::{FakeChip, FakeLines};
+
+use gpio16bit::{ReadWritePort, WritePort};
+
+/// Lines requested together, all of them either outputs or inputs.
+pub trait Lines {
+    type Error: core::fmt::Debug;
+    fn set_values(&mut self, values: &[u8]) -> Result<(), Self::Error>;
+    fn get_values(&mut self, values: &mut [u8]) -> Result<(), Self::Error>;
+    // outputs start at `values`
+    fn set_output(&mut self, values: &[u8]) -> Result<(), Self::Error>;
+    fn set_input(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Data port on 16 lines, D0 first.
+///
+/// The port traits can't report errors, so a failing request panics.
+pub struct LinePort<L> {
+    lines: L,
+    output: bool,
+}
+
+impl<L: Lines> LinePort<L> {
+    // `lines` have to be requested as outputs
+    pub fn new(lines: L) -> Self {
+        Self { lines, output: true }
+    }
+
+    pub fn release(self) -> L {
+        self.lines
+    }
+}
+
+impl<L: Lines> WritePort for LinePort<L> {
+    fn set_value(&mut self, value: u16) {
+        let mut values = [0; 16];
+        for (i, line) in values.iter_mut().enumerate() {
+            *line = u8::from(value & 1 << i != 0);
+        }
+        self.lines.set_values(&values).unwrap();
+    }
+}
+
+impl<L: Lines> ReadWritePort for LinePort<L> {
+    fn get_value(&mut self) -> u16 {
+        let mut values = [0; 16];
+        self.lines.get_values(&mut values).unwrap();
+        values.iter().enumerate().fold(0, |value, (i, &line)| value | u16::from(line != 0) << i)
+    }
+    fn dir_read(&mut self) {
+        if self.output {
+            self.lines.set_input().unwrap();
+            self.output = false;
+        }
+    }
+    fn dir_write(&mut self) {
+        if !self.output {
+            self.lines.set_output(&[0; 16]).unwrap();
+            self.output = true;
+        }
+    }
+}
+
+/// DC, WR, RD, CS, ... on a single output line.
+pub struct LinePin<L> {
+    line: L,
+}
+
+impl<L: Lines> LinePin<L> {
+    pub fn new(line: L) -> Self {
+        Self { line }
+    }
+
+    pub fn release(self) -> L {
+        self.line
+    }
+}
+
+impl<L: Lines> embedded_hal::digital::v2::OutputPin for LinePin<L> {
+    type Error = L::Error;
+    fn set_low(&mut self) -> Result<(), L::Error> {
+        self.line.set_values(&[0])
+    }
+    fn set_high(&mut self) -> Result<(), L::Error> {
+        self.line.set_values(&[1])
+    }
+}
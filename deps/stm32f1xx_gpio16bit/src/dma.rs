@@ -0,0 +1,314 @@
+//! Data streaming for parts without FSMC, like the F103C8: TIM1 generates the WR strobe on its
+//! channel 1 (PA8) and DMA1 channel 2 stores the next word into the port's ODR before every
+//! strobe, so the CPU is free while a pixel buffer or a fill goes out. The store covers all 16 pins,
+//! so the bus has to be a `FullPort`, masked ports can't be streamed to.
+//!
+//! Commands and reads stay bit-banged by `GpioWriteOnly16BitInterface`/`GpioReadWrite16BitInterface`,
+//! which get the `Strobe` in place of their WR pin.
+
+use core::{
+    convert::{Infallible, TryFrom},
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering},
+};
+
+use stm32f1xx_hal::{
+    dma::dma1::C2,
+    gpio::{gpioa::PA8, Alternate, PushPull},
+    pac::{dma1, tim1, DMA1, RCC, TIM1},
+    rcc::APB2,
+};
+
+use crate::{masked::Gpio, FullPort};
+
+// TIM1 CR1
+const CEN: u32 = 1;
+const URS: u32 = 1 << 2;
+const OPM: u32 = 1 << 3;
+// TIM1 SR and DIER
+const UIF: u32 = 1;
+const UIE: u32 = 1;
+const CC1DE: u32 = 1 << 9;
+// OC1M values in TIM1 CCMR1
+const FORCE_LOW: u32 = 0b100;
+const FORCE_HIGH: u32 = 0b101;
+const PWM1: u32 = 0b110;
+// DMA CCR
+const EN: u32 = 1;
+const DIR: u32 = 1 << 4;
+const MINC: u32 = 1 << 7;
+
+// TIM1's repetition counter is 8 bits wide, longer transfers are sent as several bursts
+const BURST: u32 = 256;
+
+// progress of the current transfer, shared with `on_interrupt`; there is only one TIM1
+static NEXT: AtomicU32 = AtomicU32::new(0);
+static REMAINING: AtomicU32 = AtomicU32::new(0);
+static INCREMENT: AtomicBool = AtomicBool::new(false);
+// source of the DMA for fills
+static FILL_VALUE: AtomicU16 = AtomicU16::new(0);
+// task waiting for the transfer to complete, woken by `on_interrupt`
+#[cfg(feature = "async")]
+static WAKER: atomic_waker::AtomicWaker = atomic_waker::AtomicWaker::new();
+
+fn tim1() -> &'static tim1::RegisterBlock {
+    unsafe { &*TIM1::ptr() }
+}
+
+fn dma1_ch2() -> &'static dma1::CH {
+    unsafe { &(*DMA1::ptr()).ch2 }
+}
+
+fn set_output_mode(mode: u32) {
+    tim1()
+        .ccmr1_output()
+        .modify(|r, w| unsafe { w.bits(r.bits() & !(0b111 << 4) | mode << 4) });
+}
+
+// starts the next burst, returns false once there is nothing left to send
+fn next_burst() -> bool {
+    let remaining = REMAINING.load(Ordering::Relaxed);
+    if remaining == 0 {
+        set_output_mode(FORCE_HIGH);
+        return false;
+    }
+    let burst = core::cmp::min(remaining, BURST);
+    let address = NEXT.load(Ordering::Relaxed);
+    let increment = if INCREMENT.load(Ordering::Relaxed) {
+        NEXT.store(address + 2 * burst, Ordering::Relaxed);
+        MINC
+    } else {
+        0
+    };
+    REMAINING.store(remaining - burst, Ordering::Relaxed);
+
+    let channel = dma1_ch2();
+    let timer = tim1();
+    unsafe {
+        channel.cr.modify(|r, w| w.bits(r.bits() & !(EN | MINC)));
+        channel.mar.write(|w| w.bits(address));
+        channel.ndtr.write(|w| w.bits(burst));
+        channel.cr.modify(|r, w| w.bits(r.bits() | increment | EN));
+        timer.rcr.write(|w| w.bits(burst - 1));
+        // UG loads RCR, URS keeps it from raising UIF
+        timer.egr.write(|w| w.bits(1));
+        timer.cr1.modify(|r, w| w.bits(r.bits() | CEN));
+    }
+    true
+}
+
+// moves on to the next burst once the timer stopped, returns true when the transfer is complete
+fn advance() -> bool {
+    let timer = tim1();
+    if timer.cr1.read().bits() & CEN != 0 {
+        return false;
+    }
+    timer.sr.write(|w| unsafe { w.bits(!UIF) });
+    !next_burst()
+}
+
+/// To be called from the TIM1_UP interrupt handler after `DmaStream::listen`.
+pub fn on_interrupt() {
+    if tim1().sr.read().bits() & UIF != 0 && advance() {
+        #[cfg(feature = "async")]
+        WAKER.wake();
+    }
+}
+
+/// WR strobe timing in TIM1 clock cycles (72MHz with the usual clock setup).
+#[derive(Clone, Copy, Debug)]
+pub struct StrobeTiming {
+    /// one word every `period` cycles, at least 2
+    pub period: u16,
+    /// WR is low for the last `low` cycles of each period and the word is latched on the rising
+    /// edge at its end; the DMA stores the word at the falling edge, so this has to cover its latency
+    pub low: u16,
+}
+
+impl Default for StrobeTiming {
+    // 6M words/s, with room for the DMA competing with the CPU for the bus
+    fn default() -> Self {
+        Self { period: 12, low: 8 }
+    }
+}
+
+/// Takes the place of the WR pin for bit-banged commands and reads by forcing the TIM1 channel 1
+/// output. Must not be toggled while a transfer is running.
+pub struct Strobe {
+    _0: (),
+}
+
+impl embedded_hal::digital::v2::OutputPin for Strobe {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        set_output_mode(FORCE_LOW);
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        set_output_mode(FORCE_HIGH);
+        Ok(())
+    }
+}
+
+/// Streams words into the ODR of `Port`'s GPIO, `Port` has to be the port of the bit-banged interface.
+///
+/// CS has to be asserted, DC high and the port in write direction for the whole transfer, e.g. by
+/// holding the interface's `Writer` after `data()`.
+pub struct DmaStream<Port> {
+    timer: TIM1,
+    channel: C2,
+    wr: PA8<Alternate<PushPull>>,
+    listening: bool,
+    _port: PhantomData<Port>,
+}
+
+impl<Port: FullPort> DmaStream<Port> {
+    // `_port` is only taken to prove that all pins of the GPIO belong to the bus
+    pub fn new(timer: TIM1, channel: C2, wr: PA8<Alternate<PushPull>>, _port: &Port, timing: StrobeTiming, _apb2: &mut APB2) -> (Self, Strobe) {
+        assert!(timing.period >= 2 && timing.low >= 1 && timing.low < timing.period);
+
+        // TIM1EN, `APB2` is only taken to make sure nobody else is modifying APB2ENR at the same time
+        unsafe { (*RCC::ptr()).apb2enr.modify(|r, w| w.bits(r.bits() | 1 << 11)) };
+        unsafe {
+            timer.cr1.write(|w| w.bits(OPM | URS));
+            timer.psc.write(|w| w.bits(0));
+            timer.arr.write(|w| w.bits(u32::from(timing.period) - 1));
+            // PWM mode 1 keeps WR high up to CCR1, which also raises the DMA request
+            timer.ccr1.write(|w| w.bits(u32::from(timing.period - timing.low)));
+            timer.ccmr1_output().write(|w| w.bits(FORCE_HIGH << 4));
+            // CC1E, MOE
+            timer.ccer.write(|w| w.bits(1));
+            timer.bdtr.write(|w| w.bits(1 << 15));
+            timer.dier.write(|w| w.bits(CC1DE));
+        }
+
+        // the GPIO registers only take word accesses, 16 bit memory to 32 bit peripheral zero extends
+        let odr = &Port::Gpio::registers().odr as *const _ as u32;
+        let channel_registers = dma1_ch2();
+        unsafe {
+            channel_registers.cr.write(|w| w.bits(0));
+            channel_registers.par.write(|w| w.bits(odr));
+            // memory to peripheral, 32 bit PSIZE, 16 bit MSIZE, high priority
+            channel_registers.cr.write(|w| w.bits(DIR | 0b10 << 8 | 0b01 << 10 | 0b10 << 12));
+        }
+
+        let stream = Self {
+            timer,
+            channel,
+            wr,
+            listening: false,
+            _port: PhantomData,
+        };
+        (stream, Strobe { _0: () })
+    }
+
+    // completion is then driven by `on_interrupt` instead of `Transfer::is_done`
+    pub fn listen(&mut self) {
+        self.timer.dier.modify(|r, w| unsafe { w.bits(r.bits() | UIE) });
+        self.listening = true;
+    }
+
+    pub fn unlisten(&mut self) {
+        self.timer.dier.modify(|r, w| unsafe { w.bits(r.bits() & !UIE) });
+        self.listening = false;
+    }
+
+    pub fn start_write<'a>(&'a mut self, values: &'a [u16]) -> Transfer<'a, Port> {
+        self.start(values.as_ptr() as u32, u32::try_from(values.len()).unwrap(), true)
+    }
+
+    pub fn start_fill(&mut self, value: u16, count: u32) -> Transfer<'_, Port> {
+        FILL_VALUE.store(value, Ordering::Relaxed);
+        self.start(&FILL_VALUE as *const _ as u32, count, false)
+    }
+
+    fn start(&mut self, address: u32, count: u32, increment: bool) -> Transfer<'_, Port> {
+        self.timer.sr.write(|w| unsafe { w.bits(!UIF) });
+        NEXT.store(address, Ordering::Relaxed);
+        REMAINING.store(count, Ordering::Relaxed);
+        INCREMENT.store(increment, Ordering::Relaxed);
+        set_output_mode(PWM1);
+        next_burst();
+        Transfer { stream: self }
+    }
+
+    pub fn release(self, _strobe: Strobe) -> (TIM1, C2, PA8<Alternate<PushPull>>) {
+        (self.timer, self.channel, self.wr)
+    }
+}
+
+impl<Port> DmaStream<Port> {
+    fn stop(&mut self) {
+        REMAINING.store(0, Ordering::Relaxed);
+        unsafe {
+            self.timer.cr1.modify(|r, w| w.bits(r.bits() & !CEN));
+            dma1_ch2().cr.modify(|r, w| w.bits(r.bits() & !EN));
+        }
+        set_output_mode(FORCE_HIGH);
+    }
+}
+
+/// A running transfer, dropping it before completion aborts the transfer.
+///
+/// Leaking it instead (`mem::forget`) lets the DMA read up to one burst past the borrow.
+pub struct Transfer<'a, Port> {
+    stream: &'a mut DmaStream<Port>,
+}
+
+impl<'a, Port> Transfer<'a, Port> {
+    pub fn is_done(&mut self) -> bool {
+        if self.stream.listening {
+            REMAINING.load(Ordering::Relaxed) == 0 && tim1().cr1.read().bits() & CEN == 0
+        } else {
+            advance()
+        }
+    }
+
+    pub fn wait(mut self) {
+        while !self.is_done() {}
+    }
+}
+
+impl<'a, Port> Drop for Transfer<'a, Port> {
+    fn drop(&mut self) {
+        self.stream.stop();
+    }
+}
+
+// completion is signalled by `on_interrupt`, so the stream has to be listening
+#[cfg(feature = "async")]
+impl<Port: FullPort> gpio16bit::asynch::BulkWrite for DmaStream<Port> {
+    type Error = Infallible;
+
+    async fn write(&mut self, values: &[u16]) -> Result<(), Infallible> {
+        assert!(self.listening, "call `listen` before streaming asynchronously");
+        let mut transfer = self.start_write(values);
+        wait(&mut transfer).await;
+        Ok(())
+    }
+
+    async fn fill(&mut self, value: u16, count: u32) -> Result<(), Infallible> {
+        assert!(self.listening, "call `listen` before streaming asynchronously");
+        let mut transfer = self.start_fill(value, count);
+        wait(&mut transfer).await;
+        Ok(())
+    }
+
+    fn abort(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(feature = "async")]
+async fn wait<Port>(transfer: &mut Transfer<'_, Port>) {
+    core::future::poll_fn(|cx| {
+        // registered before checking, so a completion in between still wakes the task
+        WAKER.register(cx.waker());
+        if transfer.is_done() {
+            core::task::Poll::Ready(())
+        } else {
+            core::task::Poll::Pending
+        }
+    })
+    .await
+}
@@ -0,0 +1,243 @@
+//! 8080 bus driven by the FSMC of high and XL density parts, as memory mapped I/O.
+//!
+//! The display's D/C input is wired to one of the FSMC address lines: commands are written to the
+//! base address of the NOR/SRAM sub-bank and data to the address with that line set. WR, RD and CS
+//! are strobed by the FSMC itself.
+//!
+//! Only sub-bank 1 (NE1 on PD7) is supported, stm32f1xx-hal has no GPIOG for the NE2..NE4 pins.
+
+use core::{cell::Cell, convert::Infallible, ptr};
+
+use gpio16bit::{NoPin, ReadWriteInterface, ReadWritePort, Reader, WriteOnlyInterface, WritePort, Writer};
+use stm32f1xx_hal::{
+    gpio::{
+        gpiod::{PD0, PD1, PD10, PD11, PD12, PD13, PD14, PD15, PD4, PD5, PD7, PD8, PD9},
+        gpioe::{PE10, PE11, PE12, PE13, PE14, PE15, PE2, PE3, PE4, PE5, PE6, PE7, PE8, PE9},
+        Alternate, PushPull,
+    },
+    pac::{FSMC, RCC},
+    rcc::AHB,
+};
+
+type AF = Alternate<PushPull>;
+
+pub type DataPins = (
+    PD14<AF>, // D0
+    PD15<AF>, // D1
+    PD0<AF>,  // D2
+    PD1<AF>,  // D3
+    PE7<AF>,  // D4
+    PE8<AF>,  // D5
+    PE9<AF>,  // D6
+    PE10<AF>, // D7
+    PE11<AF>, // D8
+    PE12<AF>, // D9
+    PE13<AF>, // D10
+    PE14<AF>, // D11
+    PE15<AF>, // D12
+    PD8<AF>,  // D13
+    PD9<AF>,  // D14
+    PD10<AF>, // D15
+);
+
+/// Address line wired to the display's D/C input.
+pub trait RegisterSelectPin {
+    const LINE: u8;
+}
+impl RegisterSelectPin for PD11<AF> {
+    const LINE: u8 = 16;
+}
+impl RegisterSelectPin for PD12<AF> {
+    const LINE: u8 = 17;
+}
+impl RegisterSelectPin for PD13<AF> {
+    const LINE: u8 = 18;
+}
+impl RegisterSelectPin for PE3<AF> {
+    const LINE: u8 = 19;
+}
+impl RegisterSelectPin for PE4<AF> {
+    const LINE: u8 = 20;
+}
+impl RegisterSelectPin for PE5<AF> {
+    const LINE: u8 = 21;
+}
+impl RegisterSelectPin for PE6<AF> {
+    const LINE: u8 = 22;
+}
+impl RegisterSelectPin for PE2<AF> {
+    const LINE: u8 = 23;
+}
+
+/// Access timings in HCLK cycles, see the FSMC_BTRx/FSMC_BWTRx registers.
+#[derive(Clone, Copy, Debug)]
+pub struct Timing {
+    /// 0..=15
+    pub address_setup: u8,
+    /// 1..=255
+    pub data_setup: u8,
+    /// 0..=15, only used for reads
+    pub bus_turnaround: u8,
+}
+
+impl Timing {
+    fn bits(&self) -> u32 {
+        assert!(self.address_setup <= 15 && self.data_setup >= 1 && self.bus_turnaround <= 15);
+        // access mode A, no address hold, clock divider and data latency are unused in SRAM mode
+        u32::from(self.bus_turnaround) << 16 | u32::from(self.data_setup) << 8 | u32::from(self.address_setup)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Timings {
+    pub read: Timing,
+    pub write: Timing,
+}
+
+impl Default for Timings {
+    // safe for the SSD1963 with HCLK at 72MHz
+    fn default() -> Self {
+        Self {
+            read: Timing {
+                address_setup: 2,
+                data_setup: 8,
+                bus_turnaround: 1,
+            },
+            write: Timing {
+                address_setup: 1,
+                data_setup: 2,
+                bus_turnaround: 0,
+            },
+        }
+    }
+}
+
+// `FsmcPort` and `RegisterSelect` share the D/C state owned by the interface, they are pointed at it
+// by `write`/`read` whenever a transaction starts and are only reachable through that transaction
+pub struct FsmcPort {
+    data_selected: *const Cell<bool>,
+    command: *mut u16,
+    data: *mut u16,
+}
+
+impl FsmcPort {
+    fn address(&self) -> *mut u16 {
+        if unsafe { (*self.data_selected).get() } {
+            self.data
+        } else {
+            self.command
+        }
+    }
+}
+
+impl WritePort for FsmcPort {
+    fn set_value(&mut self, value: u16) {
+        unsafe { ptr::write_volatile(self.address(), value) };
+    }
+}
+
+impl ReadWritePort for FsmcPort {
+    fn get_value(&mut self) -> u16 {
+        unsafe { ptr::read_volatile(self.address()) }
+    }
+    // the FSMC switches the data lines on its own
+    fn dir_write(&mut self) {}
+    fn dir_read(&mut self) {}
+}
+
+/// Stands in for the D/C pin, selects which address `FsmcPort` accesses.
+pub struct RegisterSelect {
+    data_selected: *const Cell<bool>,
+}
+
+impl embedded_hal::digital::v2::OutputPin for RegisterSelect {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        unsafe { (*self.data_selected).set(false) };
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        unsafe { (*self.data_selected).set(true) };
+        Ok(())
+    }
+}
+
+pub struct Fsmc16BitInterface<RS> {
+    fsmc: FSMC,
+    pins: (DataPins, PD4<AF>, PD5<AF>, PD7<AF>, RS),
+    data_selected: Cell<bool>,
+    port: FsmcPort,
+    dc: RegisterSelect,
+    // strobed by the FSMC
+    wr: NoPin<Infallible>,
+    rd: NoPin<Infallible>,
+    cs: NoPin<Infallible>,
+}
+
+impl<RS: RegisterSelectPin> Fsmc16BitInterface<RS> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(fsmc: FSMC, data: DataPins, noe: PD4<AF>, nwe: PD5<AF>, ne1: PD7<AF>, rs: RS, timings: Timings, _ahb: &mut AHB) -> Self {
+        // FSMCEN, `AHB` is only taken to make sure nobody else is modifying AHBENR at the same time
+        unsafe { (*RCC::ptr()).ahbenr.modify(|r, w| w.bits(r.bits() | 1 << 8)) };
+
+        // MBKEN, 16 bit wide SRAM, WREN, EXTMOD (separate write timings), bit 7 is reserved and set
+        const BCR: u32 = 1 << 14 | 1 << 12 | 1 << 7 | 0b01 << 4 | 1;
+        let (read, write) = (timings.read.bits(), timings.write.bits());
+        unsafe {
+            fsmc.btr1.write(|w| w.bits(read));
+            fsmc.bwtr1.write(|w| w.bits(write));
+            fsmc.bcr1.write(|w| w.bits(BCR));
+        }
+
+        // sub-bank 1 starts at 0x6000_0000, HADDR is shifted by one on a 16 bit bus
+        let command_address = 0x6000_0000;
+        let data_address = command_address + (1 << (RS::LINE + 1));
+        Self {
+            fsmc,
+            pins: (data, noe, nwe, ne1, rs),
+            data_selected: Cell::new(false),
+            port: FsmcPort {
+                data_selected: ptr::null(),
+                command: command_address as *mut u16,
+                data: data_address as *mut u16,
+            },
+            dc: RegisterSelect { data_selected: ptr::null() },
+            wr: NoPin::new(),
+            rd: NoPin::new(),
+            cs: NoPin::new(),
+        }
+    }
+
+    // leaves the sub-bank enabled, the FSMC clock is shared with the other sub-banks
+    pub fn release(self) -> (FSMC, DataPins, PD4<AF>, PD5<AF>, PD7<AF>, RS) {
+        let (data, noe, nwe, ne1, rs) = self.pins;
+        (self.fsmc, data, noe, nwe, ne1, rs)
+    }
+
+    // the interface may have moved since the last transaction
+    fn share_data_selected(&mut self) {
+        self.port.data_selected = &self.data_selected;
+        self.dc.data_selected = &self.data_selected;
+    }
+}
+
+impl<RS: RegisterSelectPin> WriteOnlyInterface for Fsmc16BitInterface<RS> {
+    type Port = FsmcPort;
+    type Error = Infallible;
+    type DC = RegisterSelect;
+    type WR = NoPin<Infallible>;
+    type CS = NoPin<Infallible>;
+    fn write(&mut self) -> Result<Writer<'_, FsmcPort, RegisterSelect, NoPin<Infallible>, NoPin<Infallible>, Infallible>, Infallible> {
+        self.share_data_selected();
+        Writer::new(&mut self.port, &mut self.dc, &mut self.wr, &mut self.cs)
+    }
+}
+
+impl<RS: RegisterSelectPin> ReadWriteInterface for Fsmc16BitInterface<RS> {
+    type Port = FsmcPort;
+    type RD = NoPin<Infallible>;
+    fn read(&mut self) -> Result<Reader<'_, FsmcPort, RegisterSelect, NoPin<Infallible>, NoPin<Infallible>, Infallible>, Infallible> {
+        self.share_data_selected();
+        Reader::new(&mut self.port, &mut self.dc, &mut self.rd, &mut self.cs)
+    }
+}
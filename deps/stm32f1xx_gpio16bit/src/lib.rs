@@ -1,366 +1,349 @@
 #![no_std]
 
+// TIM1 is missing on the STM32F101
+#[cfg(any(feature = "stm32f100", feature = "stm32f103", feature = "stm32f105", feature = "stm32f107"))]
+pub mod dma;
+#[cfg(any(feature = "high", feature = "xl"))]
+pub mod fsmc;
+pub mod masked;
+
 pub use gpio16bit;
-use gpio16bit::{ReadWritePort, WritePort};
-use stm32f1xx_hal::gpio::{
-    gpioa::{CRH as ACRH, CRL as ACRL, PA0, PA1, PA10, PA11, PA12, PA13, PA14, PA15, PA2, PA3, PA4, PA5, PA6, PA7, PA8, PA9},
-    gpiob::{CRH as BCRH, CRL as BCRL, PB0, PB1, PB10, PB11, PB12, PB13, PB14, PB15, PB2, PB3, PB4, PB5, PB6, PB7, PB8, PB9},
-    gpioc::{CRH as CCRH, CRL as CCRL, PC0, PC1, PC10, PC11, PC12, PC13, PC14, PC15, PC2, PC3, PC4, PC5, PC6, PC7, PC8, PC9},
-    Floating, Input, Output, PushPull,
-};
+pub use masked::{MaskedPort, MaskedRwPort};
 
-const INPUT: u32 = 0b_0100_0100_0100_0100_0100_0100_0100_0100; // Input<Floating>
-const OUTPUT: u32 = 0b_0011_0011_0011_0011_0011_0011_0011_0011; // Output<PushPull>
+use masked::Gpio;
 
-pub struct PortA;
-impl PortA {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PA0<Output<PushPull>>,
-        p1: PA1<Output<PushPull>>,
-        p2: PA2<Output<PushPull>>,
-        p3: PA3<Output<PushPull>>,
-        p4: PA4<Output<PushPull>>,
-        p5: PA5<Output<PushPull>>,
-        p6: PA6<Output<PushPull>>,
-        p7: PA7<Output<PushPull>>,
-        p8: PA8<Output<PushPull>>,
-        p9: PA9<Output<PushPull>>,
-        p10: PA10<Output<PushPull>>,
-        p11: PA11<Output<PushPull>>,
-        p12: PA12<Output<PushPull>>,
-        p13: PA13<Output<PushPull>>,
-        p14: PA14<Output<PushPull>>,
-        p15: PA15<Output<PushPull>>,
-    ) -> Self {
-        Self
-    }
-
-    #[allow(unused_variables)]
-    // TODO: find a better name
-    pub fn new2(
-        p0: PA0<Input<Floating>>,
-        p1: PA1<Input<Floating>>,
-        p2: PA2<Input<Floating>>,
-        p3: PA3<Input<Floating>>,
-        p4: PA4<Input<Floating>>,
-        p5: PA5<Input<Floating>>,
-        p6: PA6<Input<Floating>>,
-        p7: PA7<Input<Floating>>,
-        p8: PA8<Input<Floating>>,
-        p9: PA9<Input<Floating>>,
-        p10: PA10<Input<Floating>>,
-        p11: PA11<Input<Floating>>,
-        p12: PA12<Input<Floating>>,
-        p13: PA13<Input<Floating>>,
-        p14: PA14<Input<Floating>>,
-        p15: PA15<Input<Floating>>,
-        crl: &mut ACRL,
-        crh: &mut ACRH,
-    ) -> Self {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-        Self
-    }
-    pub fn into_rw(self, crl: ACRL, crh: ACRH) -> RwPortA {
-        let mut p = RwPortA(crl, crh);
-        p.dir_read();
-        p
-    }
-}
-impl WritePort for PortA {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOA::ptr()).odr.write(|w| w.bits(value as u32)) };
-    }
+// pins of a read-write bus, in the mode of its direction
+enum Direction<I, O> {
+    Read(I),
+    Write(O),
 }
 
-pub struct RwPortA(ACRL, ACRH);
-impl RwPortA {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PA0<Input<Floating>>,
-        p1: PA1<Input<Floating>>,
-        p2: PA2<Input<Floating>>,
-        p3: PA3<Input<Floating>>,
-        p4: PA4<Input<Floating>>,
-        p5: PA5<Input<Floating>>,
-        p6: PA6<Input<Floating>>,
-        p7: PA7<Input<Floating>>,
-        p8: PA8<Input<Floating>>,
-        p9: PA9<Input<Floating>>,
-        p10: PA10<Input<Floating>>,
-        p11: PA11<Input<Floating>>,
-        p12: PA12<Input<Floating>>,
-        p13: PA13<Input<Floating>>,
-        p14: PA14<Input<Floating>>,
-        p15: PA15<Input<Floating>>,
-        crl: ACRL,
-        crh: ACRH,
-    ) -> Self {
-        Self(crl, crh)
-    }
-    pub fn into_ro(mut self) -> (PortA, ACRL, ACRH) {
-        <Self as ReadWritePort>::dir_write(&mut self);
-        (PortA, self.0, self.1)
-    }
-}
-impl WritePort for RwPortA {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOA::ptr()).odr.write(|w| w.bits(value.into())) };
-    }
-}
-impl ReadWritePort for RwPortA {
-    fn get_value(&mut self) -> u16 {
-        // TODO: try to find alternative without "as"
-        unsafe { (&*stm32f1xx_hal::pac::GPIOA::ptr()).idr.read().bits() as u16 }
-    }
-    fn dir_read(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crl.write(|w| w.bits(INPUT));
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crh.write(|w| w.bits(INPUT));
-        };
-    }
-    fn dir_write(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOA::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-    }
+/// Port type owning all 16 pins of its GPIO, so storing into the ODR can't change a pin that is not
+/// part of the bus.
+pub trait FullPort {
+    type Gpio: Gpio;
 }
 
-pub struct PortB;
-impl PortB {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PB0<Output<PushPull>>,
-        p1: PB1<Output<PushPull>>,
-        p2: PB2<Output<PushPull>>,
-        p3: PB3<Output<PushPull>>,
-        p4: PB4<Output<PushPull>>,
-        p5: PB5<Output<PushPull>>,
-        p6: PB6<Output<PushPull>>,
-        p7: PB7<Output<PushPull>>,
-        p8: PB8<Output<PushPull>>,
-        p9: PB9<Output<PushPull>>,
-        p10: PB10<Output<PushPull>>,
-        p11: PB11<Output<PushPull>>,
-        p12: PB12<Output<PushPull>>,
-        p13: PB13<Output<PushPull>>,
-        p14: PB14<Output<PushPull>>,
-        p15: PB15<Output<PushPull>>,
-    ) -> Self {
-        Self
-    }
-
-    #[allow(unused_variables)]
-    // TODO: find a better name
-    pub fn new2(
-        p0: PB0<Input<Floating>>,
-        p1: PB1<Input<Floating>>,
-        p2: PB2<Input<Floating>>,
-        p3: PB3<Input<Floating>>,
-        p4: PB4<Input<Floating>>,
-        p5: PB5<Input<Floating>>,
-        p6: PB6<Input<Floating>>,
-        p7: PB7<Input<Floating>>,
-        p8: PB8<Input<Floating>>,
-        p9: PB9<Input<Floating>>,
-        p10: PB10<Input<Floating>>,
-        p11: PB11<Input<Floating>>,
-        p12: PB12<Input<Floating>>,
-        p13: PB13<Input<Floating>>,
-        p14: PB14<Input<Floating>>,
-        p15: PB15<Input<Floating>>,
-        crl: &mut BCRL,
-        crh: &mut BCRH,
-    ) -> Self {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-        Self
-    }
-
-    pub fn into_rw(self, crl: BCRL, crh: BCRH) -> RwPortB {
-        let mut p = RwPortB(crl, crh);
-        p.dir_read();
-        p
-    }
-}
-impl WritePort for PortB {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOB::ptr()).odr.write(|w| w.bits(value as u32)) };
-    }
-}
+// CRL/CRH values with every pin in that mode, for the ports the hal doesn't split into pins
+#[cfg(any(feature = "high", feature = "xl"))]
+const INPUT: u32 = 0b_0100_0100_0100_0100_0100_0100_0100_0100; // Input<Floating>
+#[cfg(any(feature = "high", feature = "xl"))]
+const OUTPUT: u32 = 0b_0011_0011_0011_0011_0011_0011_0011_0011; // Output<PushPull>
 
-pub struct RwPortB(BCRL, BCRH);
-impl RwPortB {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PB0<Input<Floating>>,
-        p1: PB1<Input<Floating>>,
-        p2: PB2<Input<Floating>>,
-        p3: PB3<Input<Floating>>,
-        p4: PB4<Input<Floating>>,
-        p5: PB5<Input<Floating>>,
-        p6: PB6<Input<Floating>>,
-        p7: PB7<Input<Floating>>,
-        p8: PB8<Input<Floating>>,
-        p9: PB9<Input<Floating>>,
-        p10: PB10<Input<Floating>>,
-        p11: PB11<Input<Floating>>,
-        p12: PB12<Input<Floating>>,
-        p13: PB13<Input<Floating>>,
-        p14: PB14<Input<Floating>>,
-        p15: PB15<Input<Floating>>,
-        crl: BCRL,
-        crh: BCRH,
-    ) -> Self {
-        Self(crl, crh)
-    }
-    pub fn into_ro(mut self) -> (PortB, BCRL, BCRH) {
-        <Self as ReadWritePort>::dir_write(&mut self);
-        (PortB, self.0, self.1)
-    }
-}
-impl WritePort for RwPortB {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOB::ptr()).odr.write(|w| w.bits(value.into())) };
-    }
-}
-impl ReadWritePort for RwPortB {
-    fn get_value(&mut self) -> u16 {
-        // TODO: try to find alternative without "as"
-        unsafe { (&*stm32f1xx_hal::pac::GPIOB::ptr()).idr.read().bits() as u16 }
-    }
-    fn dir_read(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crl.write(|w| w.bits(INPUT));
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crh.write(|w| w.bits(INPUT));
-        };
-    }
-    fn dir_write(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOB::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-    }
+// sets the `mask`ed pins to `value` and resets the rest of them in a single store
+fn bsrr(value: u16, mask: u16) -> u32 {
+    u32::from(!value & mask) << 16 | u32::from(value & mask)
 }
 
-pub struct PortC;
-impl PortC {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PC0<Output<PushPull>>,
-        p1: PC1<Output<PushPull>>,
-        p2: PC2<Output<PushPull>>,
-        p3: PC3<Output<PushPull>>,
-        p4: PC4<Output<PushPull>>,
-        p5: PC5<Output<PushPull>>,
-        p6: PC6<Output<PushPull>>,
-        p7: PC7<Output<PushPull>>,
-        p8: PC8<Output<PushPull>>,
-        p9: PC9<Output<PushPull>>,
-        p10: PC10<Output<PushPull>>,
-        p11: PC11<Output<PushPull>>,
-        p12: PC12<Output<PushPull>>,
-        p13: PC13<Output<PushPull>>,
-        p14: PC14<Output<PushPull>>,
-        p15: PC15<Output<PushPull>>,
-    ) -> Self {
-        Self
-    }
-    #[allow(unused_variables)]
-    // TODO: find a better name
-    pub fn new2(
-        p0: PC0<Input<Floating>>,
-        p1: PC1<Input<Floating>>,
-        p2: PC2<Input<Floating>>,
-        p3: PC3<Input<Floating>>,
-        p4: PC4<Input<Floating>>,
-        p5: PC5<Input<Floating>>,
-        p6: PC6<Input<Floating>>,
-        p7: PC7<Input<Floating>>,
-        p8: PC8<Input<Floating>>,
-        p9: PC9<Input<Floating>>,
-        p10: PC10<Input<Floating>>,
-        p11: PC11<Input<Floating>>,
-        p12: PC12<Input<Floating>>,
-        p13: PC13<Input<Floating>>,
-        p14: PC14<Input<Floating>>,
-        p15: PC15<Input<Floating>>,
-        crl: &mut CCRL,
-        crh: &mut CCRH,
-    ) -> Self {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-        Self
-    }
-    pub fn into_rw(self, crl: BCRL, crh: BCRH) -> RwPortB {
-        let mut p = RwPortB(crl, crh);
-        p.dir_read();
-        p
-    }
-}
-impl WritePort for PortC {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOC::ptr()).odr.write(|w| w.bits(value as u32)) };
-    }
-}
+macro_rules! port {
+    ($GPIOX:ident, $gpiox:ident, $PortX:ident, $RwPortX:ident, [$($pi:ident: $PXi:ident<$Mi:ident>: $i:literal: $cr:ident),+]) => {
+        pub use $gpiox::{$PortX, $RwPortX};
 
-pub struct RwPortC(CCRL, CCRH);
-impl RwPortC {
-    #[allow(unused_variables)]
-    pub fn new(
-        p0: PC0<Input<Floating>>,
-        p1: PC1<Input<Floating>>,
-        p2: PC2<Input<Floating>>,
-        p3: PC3<Input<Floating>>,
-        p4: PC4<Input<Floating>>,
-        p5: PC5<Input<Floating>>,
-        p6: PC6<Input<Floating>>,
-        p7: PC7<Input<Floating>>,
-        p8: PC8<Input<Floating>>,
-        p9: PC9<Input<Floating>>,
-        p10: PC10<Input<Floating>>,
-        p11: PC11<Input<Floating>>,
-        p12: PC12<Input<Floating>>,
-        p13: PC13<Input<Floating>>,
-        p14: PC14<Input<Floating>>,
-        p15: PC15<Input<Floating>>,
-        crl: CCRL,
-        crh: CCRH,
-    ) -> Self {
-        Self(crl, crh)
-    }
-    pub fn into_ro(mut self) -> (PortC, CCRL, CCRH) {
-        <Self as ReadWritePort>::dir_write(&mut self);
-        (PortC, self.0, self.1)
-    }
-}
-impl WritePort for RwPortC {
-    fn set_value(&mut self, value: u16) {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOC::ptr()).odr.write(|w| w.bits(value.into())) };
-    }
+        pub mod $gpiox {
+            use core::convert::TryFrom;
+
+            use gpio16bit::{ReadWritePort, WritePort};
+            use stm32f1xx_hal::{
+                gpio::{
+                    $gpiox::{$($PXi,)+ CRH, CRL},
+                    Active, Floating, Input, Output, PushPull,
+                },
+                pac::{gpioa::RegisterBlock, $GPIOX},
+            };
+
+            use crate::{
+                bsrr,
+                masked::{DataPin, Gpio, SplitGpio},
+                Direction, FullPort,
+            };
+
+            impl Gpio for $GPIOX {
+                fn registers() -> &'static RegisterBlock {
+                    unsafe { &*$GPIOX::ptr() }
+                }
+            }
+
+            impl SplitGpio for $GPIOX {
+                type Crl = CRL;
+                type Crh = CRH;
+            }
+
+            $(
+                impl<MODE: Active> DataPin<$GPIOX> for $PXi<MODE> {
+                    const INDEX: u8 = $i;
+                    type Input = $PXi<Input<Floating>>;
+                    type Output = $PXi<Output<PushPull>>;
+                    fn into_input(self, crl: &mut CRL, crh: &mut CRH) -> Self::Input {
+                        self.into_floating_input(ConfigRegisters { crl, crh }.$cr)
+                    }
+                    fn into_output(self, crl: &mut CRL, crh: &mut CRH) -> Self::Output {
+                        self.into_push_pull_output(ConfigRegisters { crl, crh }.$cr)
+                    }
+                }
+            )+
+
+            impl FullPort for $PortX {
+                type Gpio = $GPIOX;
+            }
+            impl FullPort for $RwPortX {
+                type Gpio = $GPIOX;
+            }
+
+            pub type OutputPins = ($($PXi<Output<PushPull>>,)+);
+            pub type InputPins = ($($PXi<Input<Floating>>,)+);
+
+            // lets `$cr` pick the register of each pin
+            struct ConfigRegisters<'a> {
+                crl: &'a mut CRL,
+                crh: &'a mut CRH,
+            }
+
+            fn into_output(pins: InputPins, cr: ConfigRegisters) -> OutputPins {
+                let ($($pi,)+) = pins;
+                ($($pi.into_push_pull_output(cr.$cr),)+)
+            }
+
+            fn into_input(pins: OutputPins, cr: ConfigRegisters) -> InputPins {
+                let ($($pi,)+) = pins;
+                ($($pi.into_floating_input(cr.$cr),)+)
+            }
+
+            pub struct $PortX(OutputPins);
+            impl $PortX {
+                // accepts the pins in any mode and switches them to Output<PushPull>
+                #[allow(clippy::too_many_arguments)]
+                pub fn new<$($Mi: Active),+>($($pi: $PXi<$Mi>,)+ crl: &mut CRL, crh: &mut CRH) -> Self {
+                    let cr = ConfigRegisters { crl, crh };
+                    Self(($($pi.into_push_pull_output(cr.$cr),)+))
+                }
+
+                pub fn into_rw(self, mut crl: CRL, mut crh: CRH) -> $RwPortX {
+                    let pins = into_input(self.0, ConfigRegisters { crl: &mut crl, crh: &mut crh });
+                    $RwPortX {
+                        pins: Some(Direction::Read(pins)),
+                        crl,
+                        crh,
+                    }
+                }
+
+                pub fn release(self) -> OutputPins {
+                    self.0
+                }
+            }
+            impl WritePort for $PortX {
+                fn set_value(&mut self, value: u16) {
+                    $GPIOX::registers().bsrr.write(|w| unsafe { w.bits(bsrr(value, 0xffff)) });
+                }
+            }
+
+            // owns CRL and CRH, so no other pin of the port can be reconfigured behind its back
+            pub struct $RwPortX {
+                // only `None` while the direction is being switched
+                pins: Option<Direction<InputPins, OutputPins>>,
+                crl: CRL,
+                crh: CRH,
+            }
+            impl $RwPortX {
+                // accepts the pins in any mode and switches them to Input<Floating>
+                #[allow(clippy::too_many_arguments)]
+                pub fn new<$($Mi: Active),+>($($pi: $PXi<$Mi>,)+ mut crl: CRL, mut crh: CRH) -> Self {
+                    let pins = {
+                        let cr = ConfigRegisters { crl: &mut crl, crh: &mut crh };
+                        ($($pi.into_floating_input(cr.$cr),)+)
+                    };
+                    Self {
+                        pins: Some(Direction::Read(pins)),
+                        crl,
+                        crh,
+                    }
+                }
+
+                pub fn into_ro(mut self) -> ($PortX, CRL, CRH) {
+                    self.dir_write();
+                    match self.pins {
+                        Some(Direction::Write(pins)) => ($PortX(pins), self.crl, self.crh),
+                        _ => unreachable!(),
+                    }
+                }
+
+                // switches the pins back to Input<Floating>
+                pub fn release(mut self) -> (InputPins, CRL, CRH) {
+                    self.dir_read();
+                    match self.pins {
+                        Some(Direction::Read(pins)) => (pins, self.crl, self.crh),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            impl WritePort for $RwPortX {
+                fn set_value(&mut self, value: u16) {
+                    $GPIOX::registers().bsrr.write(|w| unsafe { w.bits(bsrr(value, 0xffff)) });
+                }
+            }
+            impl ReadWritePort for $RwPortX {
+                fn get_value(&mut self) -> u16 {
+                    // the upper half of IDR is reserved and reads as 0
+                    u16::try_from($GPIOX::registers().idr.read().bits()).unwrap()
+                }
+                fn dir_read(&mut self) {
+                    self.pins = match self.pins.take() {
+                        Some(Direction::Write(pins)) => {
+                            let cr = ConfigRegisters {
+                                crl: &mut self.crl,
+                                crh: &mut self.crh,
+                            };
+                            Some(Direction::Read(into_input(pins, cr)))
+                        }
+                        pins => pins,
+                    };
+                }
+                fn dir_write(&mut self) {
+                    self.pins = match self.pins.take() {
+                        Some(Direction::Read(pins)) => {
+                            let cr = ConfigRegisters {
+                                crl: &mut self.crl,
+                                crh: &mut self.crh,
+                            };
+                            Some(Direction::Write(into_output(pins, cr)))
+                        }
+                        pins => pins,
+                    };
+                }
+            }
+        }
+    };
 }
-impl ReadWritePort for RwPortC {
-    fn get_value(&mut self) -> u16 {
-        unsafe { (&*stm32f1xx_hal::pac::GPIOC::ptr()).idr.read().bits() as u16 }
-    }
-    fn dir_read(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crl.write(|w| w.bits(INPUT));
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crh.write(|w| w.bits(INPUT));
-        };
-    }
-    fn dir_write(&mut self) {
-        unsafe {
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crl.write(|w| w.bits(OUTPUT));
-            (&*stm32f1xx_hal::pac::GPIOC::ptr()).crh.write(|w| w.bits(OUTPUT));
-        };
-    }
+
+// ports stm32f1xx-hal has no pins for: the bus owns the whole GPIO and writes its CRL/CRH itself, so
+// there are no masked ports on them
+#[cfg(any(feature = "high", feature = "xl"))]
+macro_rules! pac_port {
+    ($GPIOX:ident, $gpiox:ident, $PortX:ident, $RwPortX:ident, $iopxen:ident) => {
+        pub use $gpiox::{$PortX, $RwPortX};
+
+        pub mod $gpiox {
+            use core::convert::TryFrom;
+
+            use gpio16bit::{ReadWritePort, WritePort};
+            use stm32f1xx_hal::{
+                pac::{gpioa::RegisterBlock, $GPIOX, RCC},
+                rcc::APB2,
+            };
+
+            use crate::{bsrr, masked::Gpio, FullPort, INPUT, OUTPUT};
+
+            impl Gpio for $GPIOX {
+                fn registers() -> &'static RegisterBlock {
+                    unsafe { &*$GPIOX::ptr() }
+                }
+            }
+
+            impl FullPort for $PortX {
+                type Gpio = $GPIOX;
+            }
+            impl FullPort for $RwPortX {
+                type Gpio = $GPIOX;
+            }
+
+            // `APB2` is only taken to make sure nobody else is modifying APB2ENR at the same time
+            fn enable(_apb2: &mut APB2) {
+                unsafe { (*RCC::ptr()).apb2enr.modify(|_, w| w.$iopxen().set_bit()) };
+            }
+
+            fn configure(gpio: &$GPIOX, mode: u32) {
+                gpio.crl.write(|w| unsafe { w.bits(mode) });
+                gpio.crh.write(|w| unsafe { w.bits(mode) });
+            }
+
+            pub struct $PortX($GPIOX);
+            impl $PortX {
+                // enables the port and switches all of its pins to Output<PushPull>
+                pub fn new(gpio: $GPIOX, apb2: &mut APB2) -> Self {
+                    enable(apb2);
+                    configure(&gpio, OUTPUT);
+                    Self(gpio)
+                }
+
+                pub fn into_rw(self) -> $RwPortX {
+                    configure(&self.0, INPUT);
+                    $RwPortX(self.0)
+                }
+
+                // leaves the pins in Output<PushPull> and the port enabled
+                pub fn release(self) -> $GPIOX {
+                    self.0
+                }
+            }
+            impl WritePort for $PortX {
+                fn set_value(&mut self, value: u16) {
+                    self.0.bsrr.write(|w| unsafe { w.bits(bsrr(value, 0xffff)) });
+                }
+            }
+
+            pub struct $RwPortX($GPIOX);
+            impl $RwPortX {
+                // enables the port and switches all of its pins to Input<Floating>
+                pub fn new(gpio: $GPIOX, apb2: &mut APB2) -> Self {
+                    enable(apb2);
+                    configure(&gpio, INPUT);
+                    Self(gpio)
+                }
+
+                pub fn into_ro(self) -> $PortX {
+                    configure(&self.0, OUTPUT);
+                    $PortX(self.0)
+                }
+
+                // switches the pins back to Input<Floating> and leaves the port enabled
+                pub fn release(self) -> $GPIOX {
+                    configure(&self.0, INPUT);
+                    self.0
+                }
+            }
+            impl WritePort for $RwPortX {
+                fn set_value(&mut self, value: u16) {
+                    self.0.bsrr.write(|w| unsafe { w.bits(bsrr(value, 0xffff)) });
+                }
+            }
+            impl ReadWritePort for $RwPortX {
+                fn get_value(&mut self) -> u16 {
+                    // the upper half of IDR is reserved and reads as 0
+                    u16::try_from(self.0.idr.read().bits()).unwrap()
+                }
+                fn dir_read(&mut self) {
+                    configure(&self.0, INPUT);
+                }
+                fn dir_write(&mut self) {
+                    configure(&self.0, OUTPUT);
+                }
+            }
+        }
+    };
 }
+
+// GPIOD and GPIOE are split by stm32f1xx-hal for every device, like the hal we have them everywhere;
+// GPIOF and GPIOG are only on the 144 pin high and XL density parts, which the hal has no pins for
+port!(GPIOA, gpioa, PortA, RwPortA, [
+    p0: PA0<M0>: 0: crl, p1: PA1<M1>: 1: crl, p2: PA2<M2>: 2: crl, p3: PA3<M3>: 3: crl, p4: PA4<M4>: 4: crl, p5: PA5<M5>: 5: crl, p6: PA6<M6>: 6: crl, p7: PA7<M7>: 7: crl,
+    p8: PA8<M8>: 8: crh, p9: PA9<M9>: 9: crh, p10: PA10<M10>: 10: crh, p11: PA11<M11>: 11: crh, p12: PA12<M12>: 12: crh, p13: PA13<M13>: 13: crh, p14: PA14<M14>: 14: crh, p15: PA15<M15>: 15: crh
+]);
+port!(GPIOB, gpiob, PortB, RwPortB, [
+    p0: PB0<M0>: 0: crl, p1: PB1<M1>: 1: crl, p2: PB2<M2>: 2: crl, p3: PB3<M3>: 3: crl, p4: PB4<M4>: 4: crl, p5: PB5<M5>: 5: crl, p6: PB6<M6>: 6: crl, p7: PB7<M7>: 7: crl,
+    p8: PB8<M8>: 8: crh, p9: PB9<M9>: 9: crh, p10: PB10<M10>: 10: crh, p11: PB11<M11>: 11: crh, p12: PB12<M12>: 12: crh, p13: PB13<M13>: 13: crh, p14: PB14<M14>: 14: crh, p15: PB15<M15>: 15: crh
+]);
+port!(GPIOC, gpioc, PortC, RwPortC, [
+    p0: PC0<M0>: 0: crl, p1: PC1<M1>: 1: crl, p2: PC2<M2>: 2: crl, p3: PC3<M3>: 3: crl, p4: PC4<M4>: 4: crl, p5: PC5<M5>: 5: crl, p6: PC6<M6>: 6: crl, p7: PC7<M7>: 7: crl,
+    p8: PC8<M8>: 8: crh, p9: PC9<M9>: 9: crh, p10: PC10<M10>: 10: crh, p11: PC11<M11>: 11: crh, p12: PC12<M12>: 12: crh, p13: PC13<M13>: 13: crh, p14: PC14<M14>: 14: crh, p15: PC15<M15>: 15: crh
+]);
+port!(GPIOD, gpiod, PortD, RwPortD, [
+    p0: PD0<M0>: 0: crl, p1: PD1<M1>: 1: crl, p2: PD2<M2>: 2: crl, p3: PD3<M3>: 3: crl, p4: PD4<M4>: 4: crl, p5: PD5<M5>: 5: crl, p6: PD6<M6>: 6: crl, p7: PD7<M7>: 7: crl,
+    p8: PD8<M8>: 8: crh, p9: PD9<M9>: 9: crh, p10: PD10<M10>: 10: crh, p11: PD11<M11>: 11: crh, p12: PD12<M12>: 12: crh, p13: PD13<M13>: 13: crh, p14: PD14<M14>: 14: crh, p15: PD15<M15>: 15: crh
+]);
+port!(GPIOE, gpioe, PortE, RwPortE, [
+    p0: PE0<M0>: 0: crl, p1: PE1<M1>: 1: crl, p2: PE2<M2>: 2: crl, p3: PE3<M3>: 3: crl, p4: PE4<M4>: 4: crl, p5: PE5<M5>: 5: crl, p6: PE6<M6>: 6: crl, p7: PE7<M7>: 7: crl,
+    p8: PE8<M8>: 8: crh, p9: PE9<M9>: 9: crh, p10: PE10<M10>: 10: crh, p11: PE11<M11>: 11: crh, p12: PE12<M12>: 12: crh, p13: PE13<M13>: 13: crh, p14: PE14<M14>: 14: crh, p15: PE15<M15>: 15: crh
+]);
+#[cfg(any(feature = "high", feature = "xl"))]
+pac_port!(GPIOF, gpiof, PortF, RwPortF, iopfen);
+#[cfg(any(feature = "high", feature = "xl"))]
+pac_port!(GPIOG, gpiog, PortG, RwPortG, iopgen);
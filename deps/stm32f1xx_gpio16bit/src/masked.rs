@@ -0,0 +1,205 @@
+//! Buses narrower than 16 bits, occupying a contiguous range of pins of one port.
+//!
+//! Only the bus pins are touched, the remaining pins of the port stay usable. The pins are switched
+//! through the CRL/CRH of the port like any other hal pin; a `MaskedRwPort` borrows them for its
+//! direction changes, so the other pins of the port can't be reconfigured while it lives.
+
+use core::convert::TryFrom;
+
+use gpio16bit::{ReadWritePort, WritePort};
+use stm32f1xx_hal::pac::gpioa::RegisterBlock;
+
+use crate::{bsrr, Direction};
+
+// implemented for every GPIO port by the `port!` macro
+pub trait Gpio {
+    fn registers() -> &'static RegisterBlock;
+}
+
+/// Port that stm32f1xx-hal splits into pins, configured through its `CRL` and `CRH`.
+pub trait SplitGpio: Gpio {
+    type Crl;
+    type Crh;
+}
+
+/// Pin `INDEX` of port `Port`, in any mode.
+pub trait DataPin<Port: SplitGpio> {
+    const INDEX: u8;
+    type Input: DataPin<Port, Input = Self::Input, Output = Self::Output>;
+    type Output: DataPin<Port, Input = Self::Input, Output = Self::Output>;
+    // Input<Floating>
+    fn into_input(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Input;
+    // Output<PushPull>
+    fn into_output(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Output;
+}
+
+/// Tuple of consecutive pins of one port, least significant bit first.
+pub trait PinGroup<Port: SplitGpio> {
+    type Input: PinGroup<Port, Input = Self::Input, Output = Self::Output>;
+    type Output: PinGroup<Port, Input = Self::Input, Output = Self::Output>;
+    // returns (index of the first pin, number of pins)
+    // panics if the pins are not consecutive and in ascending order
+    fn range() -> (u8, u8);
+    fn into_input(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Input;
+    fn into_output(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Output;
+}
+
+macro_rules! pin_group {
+    ($($P:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<Port: SplitGpio, $($P: DataPin<Port>),+> PinGroup<Port> for ($($P,)+) {
+            type Input = ($($P::Input,)+);
+            type Output = ($($P::Output,)+);
+            fn range() -> (u8, u8) {
+                let indices = [$(<$P as DataPin<Port>>::INDEX),+];
+                for (offset, &index) in (0u8..).zip(indices.iter()) {
+                    assert!(index == indices[0] + offset, "bus pins must be consecutive, in ascending order");
+                }
+                (indices[0], indices.len() as u8)
+            }
+            fn into_input(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Input {
+                let ($($P,)+) = self;
+                ($($P.into_input(crl, crh),)+)
+            }
+            fn into_output(self, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self::Output {
+                let ($($P,)+) = self;
+                ($($P.into_output(crl, crh),)+)
+            }
+        }
+    };
+}
+
+macro_rules! pin_groups {
+    ($first:ident $(, $rest:ident)*) => {
+        pin_group!($first $(, $rest)*);
+        pin_groups!($($rest),*);
+    };
+    () => {};
+}
+
+pin_groups!(P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15);
+
+#[derive(Clone, Copy)]
+struct Range {
+    shift: u8,
+    mask: u16,
+}
+
+impl Range {
+    fn new<Port: SplitGpio, Pins: PinGroup<Port>>() -> Self {
+        let (shift, width) = Pins::range();
+        let mask = ((1u32 << width) - 1) << shift;
+        Self { shift, mask: mask as u16 }
+    }
+
+    fn set_value<Port: Gpio>(&self, value: u16) {
+        // bits above the bus width are dropped by the mask
+        let value = ((u32::from(value) << self.shift) & u32::from(self.mask)) as u16;
+        unsafe { Port::registers().bsrr.write(|w| w.bits(bsrr(value, self.mask))) };
+    }
+
+    fn get_value<Port: Gpio>(&self) -> u16 {
+        u16::try_from((Port::registers().idr.read().bits() & u32::from(self.mask)) >> self.shift).unwrap()
+    }
+}
+
+/// Write-only bus on some of the pins of `Port`, e.g.
+/// `MaskedPort::new((pb4, pb5, ..., pb11), &mut gpiob.crl, &mut gpiob.crh)`.
+pub struct MaskedPort<Port: SplitGpio, Pins: PinGroup<Port>> {
+    range: Range,
+    pins: Pins::Output,
+}
+
+impl<Port: SplitGpio, Pins: PinGroup<Port>> MaskedPort<Port, Pins> {
+    // accepts the pins in any mode and switches them to Output<PushPull>
+    pub fn new(pins: Pins, crl: &mut Port::Crl, crh: &mut Port::Crh) -> Self {
+        Self {
+            range: Range::new::<Port, Pins>(),
+            pins: pins.into_output(crl, crh),
+        }
+    }
+
+    // switches the pins to Input<Floating>, CRL and CRH stay borrowed for the direction changes
+    pub fn into_rw<'a>(self, crl: &'a mut Port::Crl, crh: &'a mut Port::Crh) -> MaskedRwPort<'a, Port, Pins> {
+        let pins = self.pins.into_input(crl, crh);
+        MaskedRwPort {
+            range: self.range,
+            pins: Some(Direction::Read(pins)),
+            crl,
+            crh,
+        }
+    }
+
+    pub fn release(self) -> Pins::Output {
+        self.pins
+    }
+}
+
+impl<Port: SplitGpio, Pins: PinGroup<Port>> WritePort for MaskedPort<Port, Pins> {
+    fn set_value(&mut self, value: u16) {
+        self.range.set_value::<Port>(value);
+    }
+}
+
+pub struct MaskedRwPort<'a, Port: SplitGpio, Pins: PinGroup<Port>> {
+    range: Range,
+    // only `None` while the direction is being switched
+    pins: Option<Direction<Pins::Input, Pins::Output>>,
+    crl: &'a mut Port::Crl,
+    crh: &'a mut Port::Crh,
+}
+
+impl<'a, Port: SplitGpio, Pins: PinGroup<Port>> MaskedRwPort<'a, Port, Pins> {
+    // accepts the pins in any mode and switches them to Input<Floating>, CRL and CRH stay borrowed for
+    // the direction changes
+    pub fn new(pins: Pins, crl: &'a mut Port::Crl, crh: &'a mut Port::Crh) -> Self {
+        let pins = pins.into_input(crl, crh);
+        Self {
+            range: Range::new::<Port, Pins>(),
+            pins: Some(Direction::Read(pins)),
+            crl,
+            crh,
+        }
+    }
+
+    pub fn into_ro(mut self) -> MaskedPort<Port, Pins> {
+        self.dir_write();
+        match self.pins {
+            Some(Direction::Write(pins)) => MaskedPort { range: self.range, pins },
+            _ => unreachable!(),
+        }
+    }
+
+    // switches the pins back to Input<Floating>
+    pub fn release(mut self) -> Pins::Input {
+        self.dir_read();
+        match self.pins {
+            Some(Direction::Read(pins)) => pins,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'a, Port: SplitGpio, Pins: PinGroup<Port>> WritePort for MaskedRwPort<'a, Port, Pins> {
+    fn set_value(&mut self, value: u16) {
+        self.range.set_value::<Port>(value);
+    }
+}
+
+impl<'a, Port: SplitGpio, Pins: PinGroup<Port>> ReadWritePort for MaskedRwPort<'a, Port, Pins> {
+    fn get_value(&mut self) -> u16 {
+        self.range.get_value::<Port>()
+    }
+    fn dir_read(&mut self) {
+        self.pins = match self.pins.take() {
+            Some(Direction::Write(pins)) => Some(Direction::Read(pins.into_input(self.crl, self.crh))),
+            pins => pins,
+        };
+    }
+    fn dir_write(&mut self) {
+        self.pins = match self.pins.take() {
+            Some(Direction::Read(pins)) => Some(Direction::Write(pins.into_output(self.crl, self.crh))),
+            pins => pins,
+        };
+    }
+}
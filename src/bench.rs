@@ -0,0 +1,90 @@
+// cycles per glyph of drawing text with and without `GlyphCache`, counted by the DWT cycle counter and
+// printed over semihosting at startup with the `bench` feature; at 72 MHz, 72 cycles are 1 us
+//
+// "sink" takes the pixels without sending them anywhere, so it counts only producing them, "display"
+// adds the bus; a cold cache expands the glyphs it sees for the first time, a warm one has them all;
+// run it on the board with `./run.sh --features bench`
+use core::{convert::TryFrom, hint::black_box, ops::RangeBounds};
+
+use cortex_m::peripheral::{DCB, DWT};
+use cortex_m_semihosting::hprintln;
+use ssd1963::Display;
+
+use crate::term::{
+    font::{MonoFont, ThisFont},
+    get_bits_transposed,
+    glyph_cache::{CachedGlyph, GlyphCache},
+};
+
+const TEXT: &str = "The quick brown fox jumps over the lazy dog 0123456789";
+const FG: u16 = 0xffff;
+const BG: u16 = 0x0010;
+
+struct Sink;
+
+impl Display for Sink {
+    type Color = u16;
+    type Error = ();
+    const WIDTH: u16 = 800;
+    const HEIGHT: u16 = 480;
+    fn fill_area<X, Y, I>(&mut self, _x: X, _y: Y, colors: &mut I) -> Result<(), ()>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+        I: Iterator<Item = u16>,
+    {
+        colors.for_each(|color| {
+            black_box(color);
+        });
+        Ok(())
+    }
+    fn fill_area_color<X: RangeBounds<u16>, Y: RangeBounds<u16>>(&mut self, _x: X, _y: Y, _color: u16) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+// `TEXT` along the top of `disp`, from `cache` or else straight from the font
+fn cycles_per_glyph<Disp>(dwt: &DWT, disp: &mut Disp, mut cache: Option<&mut GlyphCache<ThisFont>>) -> u32
+where
+    Disp: Display<Color = u16>,
+{
+    let (width, height) = (u16::from(ThisFont::CHAR_WIDTH), u16::from(ThisFont::CHAR_HEIGHT));
+    let start = dwt.cyccnt.read();
+    for (x, ch) in (0..).step_by(usize::from(width)).zip(TEXT.chars()) {
+        let (x, y) = (x..x + width, 0..height);
+        match &mut cache {
+            Some(cache) => disp.fill_area(x, y, &mut cache.get(ch, FG, BG).iter().copied()),
+            None => disp.fill_area(x, y, &mut get_bits_transposed(&ThisFont, ch).map(|bit| if bit { FG } else { BG })),
+        }
+        .ok();
+    }
+    dwt.cyccnt.read().wrapping_sub(start) / u32::try_from(TEXT.len()).unwrap()
+}
+
+// cold and warm, with a new cache in `buffer`
+fn cached<Disp>(dwt: &DWT, disp: &mut Disp, buffer: &mut [u16]) -> (u32, u32)
+where
+    Disp: Display<Color = u16>,
+{
+    let mut slots = [None::<CachedGlyph>; 64];
+    let mut cache = GlyphCache::new(&ThisFont, buffer, &mut slots);
+    let cold = cycles_per_glyph(dwt, disp, Some(&mut cache));
+    let warm = cycles_per_glyph(dwt, disp, Some(&mut cache));
+    (cold, warm)
+}
+
+pub fn run<Disp>(disp: &mut Disp, buffer: &mut [u16], mut dcb: DCB, mut dwt: DWT)
+where
+    Disp: Display<Color = u16>,
+{
+    dcb.enable_trace();
+    dwt.enable_cycle_counter();
+    let font = (cycles_per_glyph(&dwt, &mut Sink, None), cycles_per_glyph(&dwt, disp, None));
+    let (cold_sink, warm_sink) = cached(&dwt, &mut Sink, buffer);
+    let (cold_display, warm_display) = cached(&dwt, disp, buffer);
+    hprintln!("cycles per glyph    sink  display").unwrap();
+    hprintln!("font bits        {:7}  {:7}", font.0, font.1).unwrap();
+    hprintln!("cold cache       {:7}  {:7}", cold_sink, cold_display).unwrap();
+    hprintln!("warm cache       {:7}  {:7}", warm_sink, warm_display).unwrap();
+    disp.fill_area_color(.., .., 0).ok();
+}
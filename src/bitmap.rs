@@ -0,0 +1,229 @@
+// 1 bit per pixel images, the font glyphs are ones too, and anti-aliased ones with more bits per pixel
+//
+// bits are LSB first, and rows (or columns) follow each other without padding like in the font data,
+// unless `padded` makes each of them start on a new byte
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    RowMajor,
+    ColumnMajor,
+}
+
+#[derive(Clone, Copy)]
+pub struct Bitmap1bpp<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    order: Order,
+    // bits from the start of one row (or column) to the next
+    stride: usize,
+    first_bit: usize,
+}
+
+impl<'a> Bitmap1bpp<'a> {
+    // panics if `data` is too short
+    pub fn new(data: &'a [u8], width: u16, height: u16, order: Order) -> Self {
+        let line = match order {
+            Order::RowMajor => usize::from(width),
+            Order::ColumnMajor => usize::from(height),
+        };
+        Self {
+            data,
+            width,
+            height,
+            order,
+            stride: line,
+            first_bit: 0,
+        }
+        .checked()
+    }
+
+    pub fn padded(mut self) -> Self {
+        self.stride = self.stride.div_ceil(8) * 8;
+        self.checked()
+    }
+
+    // starts at a bit within the first byte or further on, for bitmaps packed one after another
+    pub fn bit_offset(mut self, first_bit: usize) -> Self {
+        self.first_bit = first_bit;
+        self.checked()
+    }
+
+    // the `width` x `height` part at `x`, `y`, sharing the data; panics if it doesn't fit
+    pub fn crop(mut self, x: u16, y: u16, width: u16, height: u16) -> Self {
+        assert!(u32::from(x) + u32::from(width) <= u32::from(self.width) && u32::from(y) + u32::from(height) <= u32::from(self.height));
+        let (line, along) = match self.order {
+            Order::RowMajor => (usize::from(y), usize::from(x)),
+            Order::ColumnMajor => (usize::from(x), usize::from(y)),
+        };
+        self.first_bit += line * self.stride + along;
+        self.width = width;
+        self.height = height;
+        self.checked()
+    }
+
+    fn checked(self) -> Self {
+        let (lines, line) = match self.order {
+            Order::RowMajor => (usize::from(self.height), usize::from(self.width)),
+            Order::ColumnMajor => (usize::from(self.width), usize::from(self.height)),
+        };
+        assert!(lines == 0 || line == 0 || self.first_bit + (lines - 1) * self.stride + line <= self.data.len() * 8);
+        self
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    // panics outside of the bitmap
+    pub fn get(&self, x: u16, y: u16) -> bool {
+        assert!(x < self.width && y < self.height);
+        let (line, along) = match self.order {
+            Order::RowMajor => (usize::from(y), usize::from(x)),
+            Order::ColumnMajor => (usize::from(x), usize::from(y)),
+        };
+        let bit = self.first_bit + line * self.stride + along;
+        self.data[bit / 8] & 1 << (bit % 8) != 0
+    }
+
+    // row by row, the order `fill_area` takes pixels in
+    pub fn bits(&self) -> Bits<'a> {
+        let (step, next_row) = match self.order {
+            Order::RowMajor => (1, self.stride),
+            Order::ColumnMajor => (self.stride, 1),
+        };
+        Bits {
+            data: self.data,
+            width: self.width,
+            rows: self.height,
+            step,
+            next_row,
+            x: 0,
+            bit: self.first_bit,
+            row_start: self.first_bit,
+        }
+    }
+}
+
+// walks the bits by offset, without the checks and multiplications of `get`
+pub struct Bits<'a> {
+    data: &'a [u8],
+    width: u16,
+    rows: u16,
+    // bits from one pixel to the next in a row, and from the start of a row to the next
+    step: usize,
+    next_row: usize,
+    x: u16,
+    bit: usize,
+    row_start: usize,
+}
+
+impl<'a> Iterator for Bits<'a> {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        if self.rows == 0 || self.width == 0 {
+            return None;
+        }
+        let bit = self.data[self.bit / 8] & 1 << (self.bit % 8) != 0;
+        self.x += 1;
+        if self.x == self.width {
+            self.x = 0;
+            self.rows -= 1;
+            self.row_start += self.next_row;
+            self.bit = self.row_start;
+        } else {
+            self.bit += self.step;
+        }
+        Some(bit)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Depth {
+    Two,
+    Four,
+}
+
+impl Depth {
+    pub fn bits(self) -> u8 {
+        match self {
+            Depth::Two => 2,
+            Depth::Four => 4,
+        }
+    }
+}
+
+// anti-aliased images with 2 or 4 bits of coverage per pixel, row by row without padding, the first
+// pixel in the low bits of a byte
+#[derive(Clone, Copy)]
+pub struct GrayBitmap<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    depth: Depth,
+}
+
+impl<'a> GrayBitmap<'a> {
+    // panics if `data` is too short
+    pub fn new(data: &'a [u8], width: u16, height: u16, depth: Depth) -> Self {
+        let bits = usize::from(width) * usize::from(height) * usize::from(depth.bits());
+        assert!(bits <= data.len() * 8);
+        Self { data, width, height, depth }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn depth(&self) -> Depth {
+        self.depth
+    }
+
+    // coverage from 0 to 15 whatever the depth; panics outside of the bitmap
+    pub fn get(&self, x: u16, y: u16) -> u8 {
+        assert!(x < self.width && y < self.height);
+        let bits = self.depth.bits();
+        let bit = (usize::from(y) * usize::from(self.width) + usize::from(x)) * usize::from(bits);
+        let value = self.data[bit / 8] >> (bit % 8) & ((1 << bits) - 1);
+        match self.depth {
+            Depth::Two => value * 5,
+            Depth::Four => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::convert::TryFrom;
+    use std::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn bits_walk_like_get() {
+        let data: Vec<u8> = (0..64u32).map(|i| u8::try_from(i * 37 % 251).unwrap()).collect();
+        for &order in &[Order::RowMajor, Order::ColumnMajor] {
+            for bitmap in [
+                Bitmap1bpp::new(&data, 13, 7, order),
+                Bitmap1bpp::new(&data, 13, 7, order).padded(),
+                Bitmap1bpp::new(&data, 13, 7, order).bit_offset(5).crop(2, 3, 9, 4),
+                Bitmap1bpp::new(&data, 0, 7, order),
+            ] {
+                let expected: Vec<bool> = (0..bitmap.height())
+                    .flat_map(|y| (0..bitmap.width()).map(move |x| bitmap.get(x, y)))
+                    .collect();
+                assert_eq!(bitmap.bits().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+}
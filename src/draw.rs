@@ -0,0 +1,858 @@
+// shapes made of as few `fill_area` windows as possible: every row of a shape is a span, and runs of
+// rows with the same span are sent as a single window; points are (x, y), may be off the display and
+// whatever is off the display is clipped
+
+use core::{
+    cmp::{max, min},
+    convert::TryFrom,
+};
+use image565::Image;
+use ssd1963::Display;
+
+use crate::bitmap::{Bitmap1bpp, GrayBitmap};
+
+pub type Point = (i32, i32);
+
+// sin of 0..=90 degrees, scaled by 1 << 14
+const SIN: [i32; 91] = [
+    0, 286, 572, 857, 1143, 1428, 1713, 1997, 2280, 2563, 2845, 3126, 3406, 3686, 3964, 4240, 4516, 4790, 5063, 5334, 5604, 5872, 6138, 6402, 6664,
+    6924, 7182, 7438, 7692, 7943, 8192, 8438, 8682, 8923, 9162, 9397, 9630, 9860, 10087, 10311, 10531, 10749, 10963, 11174, 11381, 11585, 11786,
+    11982, 12176, 12365, 12551, 12733, 12911, 13085, 13255, 13421, 13583, 13741, 13894, 14044, 14189, 14330, 14466, 14598, 14726, 14849, 14968,
+    15082, 15191, 15296, 15396, 15491, 15582, 15668, 15749, 15826, 15897, 15964, 16026, 16083, 16135, 16182, 16225, 16262, 16294, 16322, 16344,
+    16362, 16374, 16382, 16384,
+];
+
+// fractional bits of the coordinates `fill_polygon_fixed` works with
+const FRACTION: u32 = 8;
+const ONE: i64 = 1 << FRACTION;
+
+fn isqrt(value: i64) -> i64 {
+    if value <= 0 {
+        return 0;
+    }
+    let mut root = value;
+    let mut next = (root + 1) / 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+// fills `x.0..=x.1`, `y.0..=y.1` clipped to the display
+fn window<Disp: Display>(disp: &mut Disp, x: (i32, i32), y: (i32, i32), color: Disp::Color) -> Result<(), Disp::Error> {
+    let x = (max(x.0, 0), min(x.1, i32::from(Disp::WIDTH) - 1));
+    let y = (max(y.0, 0), min(y.1, i32::from(Disp::HEIGHT) - 1));
+    if x.0 > x.1 || y.0 > y.1 {
+        return Ok(());
+    }
+    disp.fill_area_color(
+        u16::try_from(x.0).unwrap()..=u16::try_from(x.1).unwrap(),
+        u16::try_from(y.0).unwrap()..=u16::try_from(y.1).unwrap(),
+        color,
+    )
+}
+
+// rows of the display a shape spanning `y0..=y1` can be seen on
+fn visible_rows<Disp: Display>(y0: i32, y1: i32) -> core::ops::RangeInclusive<i32> {
+    max(y0, 0)..=min(y1, i32::from(Disp::HEIGHT) - 1)
+}
+
+// collects spans row by row and sends a run of rows with the same span as one window
+struct Spans<Color> {
+    color: Color,
+    // x_start, x_end, y_start, y_end
+    run: Option<(i32, i32, i32, i32)>,
+}
+
+impl<Color: Copy> Spans<Color> {
+    fn new(color: Color) -> Self {
+        Self { color, run: None }
+    }
+
+    fn push<Disp: Display<Color = Color>>(&mut self, disp: &mut Disp, x0: i32, x1: i32, y: i32) -> Result<(), Disp::Error> {
+        if let Some(run) = &mut self.run {
+            if run.0 == x0 && run.1 == x1 && run.3 + 1 == y {
+                run.3 = y;
+                return Ok(());
+            }
+        }
+        self.flush(disp)?;
+        self.run = Some((x0, x1, y, y));
+        Ok(())
+    }
+
+    fn flush<Disp: Display<Color = Color>>(&mut self, disp: &mut Disp) -> Result<(), Disp::Error> {
+        match self.run.take() {
+            Some((x0, x1, y0, y1)) => window(disp, (x0, x1), (y0, y1), self.color),
+            None => Ok(()),
+        }
+    }
+}
+
+// a filled shape given by the span of each of its rows `y0..=y1`
+fn fill_rows<Disp, F>(disp: &mut Disp, y0: i32, y1: i32, extent: F, color: Disp::Color) -> Result<(), Disp::Error>
+where
+    Disp: Display,
+    F: Fn(i32) -> (i32, i32),
+{
+    let mut spans = Spans::new(color);
+    for y in visible_rows::<Disp>(y0, y1) {
+        let (x0, x1) = extent(y);
+        spans.push(disp, x0, x1, y)?;
+    }
+    spans.flush(disp)
+}
+
+// the pixels of a convex shape that have a neighbour outside of it, at most a span on the left and one on
+// the right of every row, which `emit` gets with 0 or 1 for the side
+fn outline_spans<Disp, F, E>(y0: i32, y1: i32, extent: F, mut emit: E) -> Result<(), Disp::Error>
+where
+    Disp: Display,
+    F: Fn(i32) -> (i32, i32),
+    E: FnMut(usize, i32, i32, i32) -> Result<(), Disp::Error>,
+{
+    let row = |y: i32| if y < y0 || y > y1 { None } else { Some(extent(y)) };
+    for y in visible_rows::<Disp>(y0, y1) {
+        let (x0, x1) = extent(y);
+        let inner = match (row(y - 1), row(y + 1)) {
+            (Some(above), Some(below)) => (max(x0 + 1, max(above.0, below.0)), min(x1 - 1, min(above.1, below.1))),
+            _ => (x1 + 1, x0 - 1),
+        };
+        if inner.0 > inner.1 {
+            emit(0, x0, x1, y)?;
+        } else {
+            emit(0, x0, inner.0 - 1, y)?;
+            emit(1, inner.1 + 1, x1, y)?;
+        }
+    }
+    Ok(())
+}
+
+fn outline_rows<Disp, F>(disp: &mut Disp, y0: i32, y1: i32, extent: F, color: Disp::Color) -> Result<(), Disp::Error>
+where
+    Disp: Display,
+    F: Fn(i32) -> (i32, i32),
+{
+    let mut spans = [Spans::new(color), Spans::new(color)];
+    outline_spans::<Disp, _, _>(y0, y1, extent, |side, x0, x1, y| spans[side].push(disp, x0, x1, y))?;
+    spans[0].flush(disp)?;
+    spans[1].flush(disp)
+}
+
+// half the width of the row `dy` off the center of a circle, including pixels the circle covers at least halfway
+fn circle_half_width(radius: i32, dy: i32) -> i32 {
+    let radius = i64::from(radius);
+    i32::try_from(isqrt(radius * radius + radius - i64::from(dy) * i64::from(dy))).unwrap()
+}
+
+fn ellipse_half_width(radii: (u16, u16), dy: i32) -> i32 {
+    let (a, b) = (i64::from(radii.0), i64::from(radii.1));
+    if b == 0 {
+        return i32::from(radii.0);
+    }
+    let dy = i64::from(dy);
+    i32::try_from(isqrt((a * a + a) * (b * b + b - dy * dy) / (b * b + b))).unwrap()
+}
+
+fn rounded_extent(top_left: Point, size: (u16, u16), radius: u16) -> impl Fn(i32) -> (i32, i32) {
+    let (x, y) = top_left;
+    let (width, height) = (i32::from(size.0), i32::from(size.1));
+    let radius = min(i32::from(radius), min(width, height) / 2);
+    move |row| {
+        let from_edge = min(row - y, y + height - 1 - row);
+        let inset = if from_edge < radius {
+            radius - circle_half_width(radius, radius - from_edge)
+        } else {
+            0
+        };
+        (x + inset, x + width - 1 - inset)
+    }
+}
+
+pub fn fill_rectangle<Disp: Display>(disp: &mut Disp, top_left: Point, size: (u16, u16), color: Disp::Color) -> Result<(), Disp::Error> {
+    if size.0 == 0 || size.1 == 0 {
+        return Ok(());
+    }
+    let (x, y) = top_left;
+    window(disp, (x, x + i32::from(size.0) - 1), (y, y + i32::from(size.1) - 1), color)
+}
+
+// the border is `thickness` pixels wide, inside of the rectangle
+pub fn rectangle<Disp: Display>(disp: &mut Disp, top_left: Point, size: (u16, u16), thickness: u16, color: Disp::Color) -> Result<(), Disp::Error> {
+    if thickness == 0 || size.0 == 0 || size.1 == 0 {
+        return Ok(());
+    }
+    if 2 * u32::from(thickness) >= u32::from(min(size.0, size.1)) {
+        return fill_rectangle(disp, top_left, size, color);
+    }
+    let (x, y) = top_left;
+    let (x_end, y_end) = (x + i32::from(size.0) - 1, y + i32::from(size.1) - 1);
+    let t = i32::from(thickness);
+    window(disp, (x, x_end), (y, y + t - 1), color)?;
+    window(disp, (x, x_end), (y_end - t + 1, y_end), color)?;
+    window(disp, (x, x + t - 1), (y + t, y_end - t), color)?;
+    window(disp, (x_end - t + 1, x_end), (y + t, y_end - t), color)
+}
+
+// `radius` is limited to half of the shorter side
+pub fn fill_rounded_rectangle<Disp: Display>(
+    disp: &mut Disp,
+    top_left: Point,
+    size: (u16, u16),
+    radius: u16,
+    color: Disp::Color,
+) -> Result<(), Disp::Error> {
+    if size.0 == 0 || size.1 == 0 {
+        return Ok(());
+    }
+    let (y, y_end) = (top_left.1, top_left.1 + i32::from(size.1) - 1);
+    fill_rows(disp, y, y_end, rounded_extent(top_left, size, radius), color)
+}
+
+pub fn rounded_rectangle<Disp: Display>(
+    disp: &mut Disp,
+    top_left: Point,
+    size: (u16, u16),
+    radius: u16,
+    color: Disp::Color,
+) -> Result<(), Disp::Error> {
+    if size.0 == 0 || size.1 == 0 {
+        return Ok(());
+    }
+    let (y, y_end) = (top_left.1, top_left.1 + i32::from(size.1) - 1);
+    outline_rows(disp, y, y_end, rounded_extent(top_left, size, radius), color)
+}
+
+pub fn fill_circle<Disp: Display>(disp: &mut Disp, center: Point, radius: u16, color: Disp::Color) -> Result<(), Disp::Error> {
+    let (cx, cy, r) = (center.0, center.1, i32::from(radius));
+    fill_rows(
+        disp,
+        cy - r,
+        cy + r,
+        |y| {
+            let half = circle_half_width(r, y - cy);
+            (cx - half, cx + half)
+        },
+        color,
+    )
+}
+
+pub fn circle<Disp: Display>(disp: &mut Disp, center: Point, radius: u16, color: Disp::Color) -> Result<(), Disp::Error> {
+    let (cx, cy, r) = (center.0, center.1, i32::from(radius));
+    outline_rows(
+        disp,
+        cy - r,
+        cy + r,
+        |y| {
+            let half = circle_half_width(r, y - cy);
+            (cx - half, cx + half)
+        },
+        color,
+    )
+}
+
+// `radii` are horizontal and vertical
+pub fn fill_ellipse<Disp: Display>(disp: &mut Disp, center: Point, radii: (u16, u16), color: Disp::Color) -> Result<(), Disp::Error> {
+    let (cx, cy) = center;
+    fill_rows(
+        disp,
+        cy - i32::from(radii.1),
+        cy + i32::from(radii.1),
+        |y| {
+            let half = ellipse_half_width(radii, y - cy);
+            (cx - half, cx + half)
+        },
+        color,
+    )
+}
+
+pub fn ellipse<Disp: Display>(disp: &mut Disp, center: Point, radii: (u16, u16), color: Disp::Color) -> Result<(), Disp::Error> {
+    let (cx, cy) = center;
+    outline_rows(
+        disp,
+        cy - i32::from(radii.1),
+        cy + i32::from(radii.1),
+        |y| {
+            let half = ellipse_half_width(radii, y - cy);
+            (cx - half, cx + half)
+        },
+        color,
+    )
+}
+
+// (cos, sin) of `degrees`, scaled by 1 << 14
+fn direction(degrees: i32) -> (i64, i64) {
+    let d = usize::try_from(degrees.rem_euclid(360)).unwrap();
+    let (cos, sin) = match d {
+        0..=90 => (SIN[90 - d], SIN[d]),
+        91..=180 => (-SIN[d - 90], SIN[180 - d]),
+        181..=270 => (-SIN[270 - d], -SIN[d - 180]),
+        _ => (SIN[d - 270], -SIN[360 - d]),
+    };
+    (i64::from(cos), i64::from(sin))
+}
+
+fn cross(a: (i64, i64), b: (i64, i64)) -> i64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+// part of the circle outline from `start` degrees, sweeping `sweep` degrees; 0 degrees points right
+// and angles go clockwise, the way the y axis points down
+pub fn arc<Disp: Display>(disp: &mut Disp, center: Point, radius: u16, start: i32, sweep: i32, color: Disp::Color) -> Result<(), Disp::Error> {
+    let (start, sweep) = if sweep < 0 { (start + sweep, -sweep) } else { (start, sweep) };
+    if sweep == 0 {
+        return Ok(());
+    }
+    if sweep >= 360 {
+        return circle(disp, center, radius, color);
+    }
+    let (from, to) = (direction(start), direction(start + sweep));
+    let inside = |x: i32, y: i32| {
+        let point = (i64::from(x - center.0), i64::from(y - center.1));
+        if sweep <= 180 {
+            cross(from, point) >= 0 && cross(point, to) >= 0
+        } else {
+            !(cross(to, point) > 0 && cross(point, from) > 0)
+        }
+    };
+
+    let (cx, cy, r) = (center.0, center.1, i32::from(radius));
+    let mut spans = [Spans::new(color), Spans::new(color)];
+    // a span of the outline is split where it leaves the arc
+    let extent = |y: i32| {
+        let half = circle_half_width(r, y - cy);
+        (cx - half, cx + half)
+    };
+    outline_spans::<Disp, _, _>(cy - r, cy + r, extent, |side, x0, x1, y| {
+        let mut piece: Option<(i32, i32)> = None;
+        for x in max(x0, 0)..=min(x1, i32::from(Disp::WIDTH) - 1) {
+            match (&mut piece, inside(x, y)) {
+                (Some(piece), true) => piece.1 = x,
+                (None, true) => piece = Some((x, x)),
+                (Some(_), false) => {
+                    let (p0, p1) = piece.take().unwrap();
+                    spans[side].push(disp, p0, p1, y)?;
+                }
+                (None, false) => {}
+            }
+        }
+        match piece {
+            Some((p0, p1)) => spans[side].push(disp, p0, p1, y),
+            None => Ok(()),
+        }
+    })?;
+    spans[0].flush(disp)?;
+    spans[1].flush(disp)
+}
+
+// x of the edge `a`-`b` at height `y`, if the edge crosses it; the lower end doesn't count, so a vertex
+// shared by two edges is crossed once
+fn crossing(a: (i64, i64), b: (i64, i64), y: i64) -> Option<i64> {
+    let (top, bottom) = if a.1 < b.1 { (a, b) } else { (b, a) };
+    if top.1 <= y && y < bottom.1 {
+        Some(top.0 + (y - top.1) * (bottom.0 - top.0) / (bottom.1 - top.1))
+    } else {
+        None
+    }
+}
+
+fn ceil_fixed(value: i64) -> i32 {
+    i32::try_from(-(-value).div_euclid(ONE)).unwrap()
+}
+
+// the point where the `points` outline crosses `y` right after `after`, ordered by x and then by edge;
+// `to_fixed` converts the points as they are walked, so they are never copied
+fn next_crossing<P: Copy>(points: &[P], to_fixed: impl Fn(P) -> (i64, i64), y: i64, after: Option<(i64, usize)>) -> Option<(i64, usize)> {
+    let mut next: Option<(i64, usize)> = None;
+    for i in 0..points.len() {
+        if let Some(x) = crossing(to_fixed(points[i]), to_fixed(points[(i + 1) % points.len()]), y) {
+            let candidate = (x, i);
+            if after.is_none_or(|after| candidate > after) && next.is_none_or(|next| candidate < next) {
+                next = Some(candidate);
+            }
+        }
+    }
+    next
+}
+
+// even-odd fill of a polygon with `FRACTION` bits of sub-pixel precision; a pixel is filled if its
+// center is inside or on a left or top edge, so polygons sharing an edge don't overlap
+fn fill_polygon_fixed<Disp, P, F>(disp: &mut Disp, points: &[P], to_fixed: F, color: Disp::Color) -> Result<(), Disp::Error>
+where
+    Disp: Display,
+    P: Copy,
+    F: Fn(P) -> (i64, i64) + Copy,
+{
+    if points.len() < 3 {
+        return Ok(());
+    }
+    let y_min = points.iter().map(|&point| to_fixed(point).1).min().unwrap();
+    let y_max = points.iter().map(|&point| to_fixed(point).1).max().unwrap();
+    let mut spans = Spans::new(color);
+    for y in visible_rows::<Disp>(ceil_fixed(y_min), ceil_fixed(y_max) - 1) {
+        let sample = i64::from(y) * ONE;
+        let mut after = None;
+        while let Some(enter) = next_crossing(points, to_fixed, sample, after) {
+            let leave = match next_crossing(points, to_fixed, sample, Some(enter)) {
+                Some(leave) => leave,
+                None => break,
+            };
+            let (x0, x1) = (ceil_fixed(enter.0), ceil_fixed(leave.0) - 1);
+            if x0 <= x1 {
+                spans.push(disp, x0, x1, y)?;
+            }
+            after = Some(leave);
+        }
+    }
+    spans.flush(disp)
+}
+
+// the polygon is closed from the last point back to the first
+pub fn fill_polygon<Disp: Display>(disp: &mut Disp, points: &[Point], color: Disp::Color) -> Result<(), Disp::Error> {
+    fill_polygon_fixed(disp, points, |point: Point| (i64::from(point.0) * ONE, i64::from(point.1) * ONE), color)
+}
+
+pub fn polygon<Disp: Display>(disp: &mut Disp, points: &[Point], color: Disp::Color) -> Result<(), Disp::Error> {
+    for (i, &from) in points.iter().enumerate() {
+        line(disp, from, points[(i + 1) % points.len()], 1, color)?;
+    }
+    Ok(())
+}
+
+pub fn fill_triangle<Disp: Display>(disp: &mut Disp, a: Point, b: Point, c: Point, color: Disp::Color) -> Result<(), Disp::Error> {
+    fill_polygon(disp, &[a, b, c], color)
+}
+
+pub fn triangle<Disp: Display>(disp: &mut Disp, a: Point, b: Point, c: Point, color: Disp::Color) -> Result<(), Disp::Error> {
+    polygon(disp, &[a, b, c], color)
+}
+
+// the part of the line from `from` to `to` within the display grown by `margin` on every side, in `ONE`
+// units; Liang-Barsky, in i128 as the points may be anywhere
+fn clip_line<Disp: Display>(from: Point, to: Point, margin: i32) -> Option<((i64, i64), (i64, i64))> {
+    let (x, y) = (i128::from(from.0), i128::from(from.1));
+    let (dx, dy) = (i128::from(to.0) - x, i128::from(to.1) - y);
+    let margin = i128::from(margin);
+    let (x_max, y_max) = (i128::from(Disp::WIDTH) - 1 + margin, i128::from(Disp::HEIGHT) - 1 + margin);
+    // the visible part lies between these fractions of the line, their denominators are positive
+    let (mut enter, mut leave) = ((0, 1), (1, 1));
+    for &(p, q) in &[(-dx, x + margin), (dx, x_max - x), (-dy, y + margin), (dy, y_max - y)] {
+        if p == 0 {
+            if q < 0 {
+                return None;
+            }
+        } else if p < 0 {
+            if -q * enter.1 > enter.0 * -p {
+                enter = (-q, -p);
+            }
+        } else if q * leave.1 < leave.0 * p {
+            leave = (q, p);
+        }
+    }
+    if enter.0 * leave.1 > leave.0 * enter.1 {
+        return None;
+    }
+    let at = |(num, den): (i128, i128)| {
+        let fixed = |start: i128, delta: i128| {
+            let doubled = (start * den + delta * num) * 2 * i128::from(ONE);
+            i64::try_from((doubled + den).div_euclid(2 * den)).unwrap()
+        };
+        (fixed(x, dx), fixed(y, dy))
+    };
+    Some((at(enter), at(leave)))
+}
+
+fn round_fixed(point: (i64, i64)) -> Point {
+    let round = |value: i64| i32::try_from((value + ONE / 2) >> FRACTION).unwrap();
+    (round(point.0), round(point.1))
+}
+
+// Bresenham takes a step along the major axis every iteration, after `steps` of them it took this many on
+// the axis the line moves `delta` along; in i128 as the points may be anywhere
+fn steps_taken(steps: i128, delta: i64, major: i64) -> i128 {
+    let (length, major) = (i128::from(delta.abs()), i128::from(major));
+    if length == major {
+        steps
+    } else {
+        (2 * steps * length + major).div_euclid(2 * major)
+    }
+}
+
+// the iterations of Bresenham's walk with the pixel within `0..limit` on the axis it moves `delta` along
+// from `start`, if any
+fn visible_steps(start: i32, delta: i64, major: i64, limit: u16) -> Option<(i128, i128)> {
+    let ceil = |value: i128, by: i128| -(-value).div_euclid(by);
+    let (start, length, major) = (i128::from(start), i128::from(delta.abs()), i128::from(major));
+    // steps on this axis that stay on the display
+    let (low, high) = (-start, i128::from(limit) - 1 - start);
+    let (low, high) = if delta < 0 { (-high, -low) } else { (low, high) };
+    let (low, high) = (max(low, 0), min(high, length));
+    if low > high {
+        return None;
+    }
+    let steps = if length == major {
+        (low, high)
+    } else if length == 0 {
+        (0, major)
+    } else {
+        (ceil(2 * major * low - major, 2 * length), ceil(2 * major * high + major, 2 * length) - 1)
+    };
+    Some((max(steps.0, 0), min(steps.1, major)))
+}
+
+// Bresenham, the pixels of a row are a single span, which makes steep lines vertical windows; the walk
+// only covers the pixels on the display
+fn thin_line<Disp: Display>(disp: &mut Disp, from: Point, to: Point, color: Disp::Color) -> Result<(), Disp::Error> {
+    let (delta_x, delta_y) = (i64::from(to.0) - i64::from(from.0), i64::from(to.1) - i64::from(from.1));
+    let major = max(delta_x.abs(), delta_y.abs());
+    let (first, last) = match (
+        visible_steps(from.0, delta_x, major, Disp::WIDTH),
+        visible_steps(from.1, delta_y, major, Disp::HEIGHT),
+    ) {
+        (Some(x), Some(y)) => (max(x.0, y.0), min(x.1, y.1)),
+        _ => return Ok(()),
+    };
+    if first > last {
+        return Ok(());
+    }
+    let (dx, dy) = (delta_x.abs(), -delta_y.abs());
+    let (step_x, step_y) = (if delta_x > 0 { 1 } else { -1 }, if delta_y > 0 { 1 } else { -1 });
+    // the state after `first` iterations, every step on an axis adds the other axis' delta to the error
+    let (taken_x, taken_y) = (steps_taken(first, delta_x, major), steps_taken(first, delta_y, major));
+    let mut x = i32::try_from(i128::from(from.0) + i128::from(step_x) * taken_x).unwrap();
+    let mut y = i32::try_from(i128::from(from.1) + i128::from(step_y) * taken_y).unwrap();
+    let mut error = i64::try_from(i128::from(dx + dy) + taken_x * i128::from(dy) + taken_y * i128::from(dx)).unwrap();
+    let mut spans = Spans::new(color);
+    let mut row = (x, x);
+    for _ in first..last {
+        let double = 2 * error;
+        if double >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double <= dx {
+            error += dx;
+            spans.push(disp, row.0, row.1, y)?;
+            y += step_y;
+            row = (x, x);
+        } else {
+            row = (min(row.0, x), max(row.1, x));
+        }
+    }
+    spans.push(disp, row.0, row.1, y)?;
+    spans.flush(disp)
+}
+
+// covers the pixels from `from` to `to` and `thickness` pixels across
+pub fn line<Disp: Display>(disp: &mut Disp, from: Point, to: Point, thickness: u16, color: Disp::Color) -> Result<(), Disp::Error> {
+    match thickness {
+        0 => return Ok(()),
+        1 => return thin_line(disp, from, to, color),
+        _ => {}
+    }
+    // nothing further than `thickness` off the display can be seen, so neither can the caps of a clipped end
+    let ((x0, y0), (x1, y1)) = match clip_line::<Disp>(from, to, i32::from(thickness)) {
+        Some(clipped) => clipped,
+        None => return Ok(()),
+    };
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let length = isqrt(dx * dx + dy * dy);
+    if length == 0 {
+        let (x, y) = round_fixed((x0, y0));
+        let t = i32::from(thickness);
+        return window(disp, (x - t / 2, x + (t - 1) / 2), (y - t / 2, y + (t - 1) / 2), color);
+    }
+    // half a pixel along the line past both ends and half the thickness across it
+    let (along_x, along_y) = (dx * ONE / 2 / length, dy * ONE / 2 / length);
+    let t = i64::from(thickness);
+    let (across_x, across_y) = (-dy * t * ONE / 2 / length, dx * t * ONE / 2 / length);
+    let (x0, y0) = (x0 - along_x, y0 - along_y);
+    let (x1, y1) = (x1 + along_x, y1 + along_y);
+    let corners = [
+        (x0 + across_x, y0 + across_y),
+        (x1 + across_x, y1 + across_y),
+        (x1 - across_x, y1 - across_y),
+        (x0 - across_x, y0 - across_y),
+    ];
+    fill_polygon_fixed(disp, &corners, |corner| corner, color)
+}
+
+// the columns and rows of a `size` block at `top_left` that are on the display
+fn visible<Disp: Display>(top_left: Point, size: (u16, u16)) -> Option<((i32, i32), (i32, i32))> {
+    let (x, y) = top_left;
+    let x_visible = (max(x, 0), min(x + i32::from(size.0) - 1, i32::from(Disp::WIDTH) - 1));
+    let y_visible = (max(y, 0), min(y + i32::from(size.1) - 1, i32::from(Disp::HEIGHT) - 1));
+    if x_visible.0 > x_visible.1 || y_visible.0 > y_visible.1 {
+        None
+    } else {
+        Some((x_visible, y_visible))
+    }
+}
+
+// a single window for the visible part of a block, `pixels` covers all of the block row by row and
+// the ones off the display are skipped
+fn fill_clipped<Disp, I>(disp: &mut Disp, top_left: Point, size: (u16, u16), pixels: I) -> Result<(), Disp::Error>
+where
+    Disp: Display,
+    I: Iterator<Item = Disp::Color>,
+{
+    let (x_visible, y_visible) = match visible::<Disp>(top_left, size) {
+        Some(visible) => visible,
+        None => return Ok(()),
+    };
+    let offset = |from: i32, to: i32| usize::try_from(from - to).unwrap();
+    let columns = offset(x_visible.0, top_left.0)..=offset(x_visible.1, top_left.0);
+    let rows = offset(y_visible.0, top_left.1)..=offset(y_visible.1, top_left.1);
+    let width = usize::from(size.0);
+    let mut pixels = pixels
+        .enumerate()
+        .filter(move |(i, _)| columns.contains(&(i % width)) && rows.contains(&(i / width)))
+        .map(|(_, pixel)| pixel);
+    disp.fill_area(
+        u16::try_from(x_visible.0).unwrap()..=u16::try_from(x_visible.1).unwrap(),
+        u16::try_from(y_visible.0).unwrap()..=u16::try_from(y_visible.1).unwrap(),
+        &mut pixels,
+    )
+}
+
+// decoded straight into a single window
+pub fn image<Disp>(disp: &mut Disp, top_left: Point, image: &Image) -> Result<(), Disp::Error>
+where
+    Disp: Display<Color = u16>,
+{
+    fill_clipped(disp, top_left, (image.width(), image.height()), image.pixels())
+}
+
+// set bits in `fg` and clear ones in `bg`, a single window; without `bg` the clear bits are left alone
+// and every run of set bits in a row is a span
+pub fn bitmap<Disp: Display>(
+    disp: &mut Disp,
+    top_left: Point,
+    bitmap: &Bitmap1bpp,
+    fg: Disp::Color,
+    bg: Option<Disp::Color>,
+) -> Result<(), Disp::Error> {
+    let size = (bitmap.width(), bitmap.height());
+    if let Some(bg) = bg {
+        return fill_clipped(disp, top_left, size, bitmap.bits().map(move |bit| if bit { fg } else { bg }));
+    }
+    let (x_visible, y_visible) = match visible::<Disp>(top_left, size) {
+        Some(visible) => visible,
+        None => return Ok(()),
+    };
+    let (x, y) = top_left;
+    let mut spans = Spans::new(fg);
+    for row in y_visible.0..=y_visible.1 {
+        let bitmap_row = u16::try_from(row - y).unwrap();
+        let mut run = None;
+        for column in x_visible.0..=x_visible.1 + 1 {
+            let set = column <= x_visible.1 && bitmap.get(u16::try_from(column - x).unwrap(), bitmap_row);
+            match (run, set) {
+                (None, true) => run = Some(column),
+                (Some(start), false) => {
+                    spans.push(disp, start, column - 1, row)?;
+                    run = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    spans.flush(disp)
+}
+
+// colors for coverage 0 to 15, worked out once for a whole text; coverage below `from` is left alone
+#[derive(Clone, Copy)]
+pub struct Ramp {
+    colors: [u16; 16],
+    from: u8,
+}
+
+impl Ramp {
+    // RGB565 blended from `bg` to `fg`, for drawing over `bg`
+    pub fn new(fg: u16, bg: u16) -> Self {
+        let channel = |color: u16, shift: u16, mask: u16| i32::from(color >> shift & mask);
+        let blend = |level: i32, shift: u16, mask: u16| {
+            let (fg, bg) = (channel(fg, shift, mask), channel(bg, shift, mask));
+            u16::try_from(bg + ((fg - bg) * level * 2 + 15).div_euclid(30)).unwrap() << shift
+        };
+        let mut colors = [0; 16];
+        for (level, color) in (0..).zip(colors.iter_mut()) {
+            *color = blend(level, 11, 0x1f) | blend(level, 5, 0x3f) | blend(level, 0, 0x1f);
+        }
+        Self { colors, from: 1 }
+    }
+
+    // without knowing what is underneath: pixels covered more than halfway are `fg`, the rest is left alone
+    pub fn solid(fg: u16) -> Self {
+        Self { colors: [fg; 16], from: 8 }
+    }
+
+    pub fn color(&self, level: u8) -> u16 {
+        self.colors[usize::from(level)]
+    }
+}
+
+// coverage through `ramp`, every run of drawn pixels in a row is a window
+pub fn coverage<Disp>(disp: &mut Disp, top_left: Point, bitmap: &GrayBitmap, ramp: &Ramp) -> Result<(), Disp::Error>
+where
+    Disp: Display<Color = u16>,
+{
+    let (x_visible, y_visible) = match visible::<Disp>(top_left, (bitmap.width(), bitmap.height())) {
+        Some(visible) => visible,
+        None => return Ok(()),
+    };
+    let (x, y) = top_left;
+    let level = |column: i32, row: i32| bitmap.get(u16::try_from(column - x).unwrap(), u16::try_from(row - y).unwrap());
+    for row in y_visible.0..=y_visible.1 {
+        let mut column = x_visible.0;
+        while column <= x_visible.1 {
+            if level(column, row) < ramp.from {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column <= x_visible.1 && level(column, row) >= ramp.from {
+                column += 1;
+            }
+            let mut colors = (start..column).map(|column| ramp.color(level(column, row)));
+            let row = u16::try_from(row).unwrap();
+            disp.fill_area(u16::try_from(start).unwrap()..=u16::try_from(column - 1).unwrap(), row..=row, &mut colors)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use core::{convert::Infallible, ops::RangeBounds};
+    use ssd1963::Bounds;
+    use std::{vec, vec::Vec};
+
+    use super::*;
+
+    // 64x48 pixels in RAM, 0 is the background
+    pub(crate) struct Framebuffer {
+        pub(crate) pixels: Vec<u16>,
+    }
+
+    impl Framebuffer {
+        pub(crate) fn new() -> Self {
+            Self {
+                pixels: vec![0; usize::from(Self::WIDTH) * usize::from(Self::HEIGHT)],
+            }
+        }
+
+        pub(crate) fn get(&self, x: u16, y: u16) -> u16 {
+            self.pixels[usize::from(y) * usize::from(Self::WIDTH) + usize::from(x)]
+        }
+
+        // the pixels that are not the background
+        pub(crate) fn drawn(&self) -> Vec<(u16, u16)> {
+            (0..Self::HEIGHT)
+                .flat_map(|y| (0..Self::WIDTH).map(move |x| (x, y)))
+                .filter(|&(x, y)| self.get(x, y) != 0)
+                .collect()
+        }
+    }
+
+    impl Display for Framebuffer {
+        type Color = u16;
+        type Error = Infallible;
+        const WIDTH: u16 = 64;
+        const HEIGHT: u16 = 48;
+
+        // panics if the window is not on the display, like the SSD1963 would misbehave
+        fn fill_area<X, Y, I>(&mut self, x: X, y: Y, iter: &mut I) -> Result<(), Infallible>
+        where
+            X: RangeBounds<u16>,
+            Y: RangeBounds<u16>,
+            I: Iterator<Item = u16>,
+        {
+            let screen = Bounds {
+                x_start: 0,
+                x_end: Self::WIDTH - 1,
+                y_start: 0,
+                y_end: Self::HEIGHT - 1,
+            };
+            let bounds = Bounds::new_within(x, y, &screen).unwrap();
+            for y in bounds.range_vert() {
+                for x in bounds.range_horiz() {
+                    self.pixels[usize::from(y) * usize::from(Self::WIDTH) + usize::from(x)] = iter.next().unwrap();
+                }
+            }
+            Ok(())
+        }
+
+        fn fill_area_color<X, Y>(&mut self, x: X, y: Y, color: u16) -> Result<(), Infallible>
+        where
+            X: RangeBounds<u16>,
+            Y: RangeBounds<u16>,
+        {
+            self.fill_area(x, y, &mut core::iter::repeat(color))
+        }
+    }
+
+    #[test]
+    fn thin_line_has_a_pixel_per_column() {
+        let mut fb = Framebuffer::new();
+        line(&mut fb, (1, 1), (20, 6), 1, 1).unwrap();
+        let drawn = fb.drawn();
+        assert_eq!(drawn.len(), 20);
+        for x in 1..=20 {
+            assert_eq!(drawn.iter().filter(|pixel| pixel.0 == x).count(), 1);
+        }
+        assert!(drawn.contains(&(1, 1)) && drawn.contains(&(20, 6)));
+    }
+
+    #[test]
+    fn thin_line_is_clipped() {
+        let mut fb = Framebuffer::new();
+        line(&mut fb, (-5, -5), (100, 100), 1, 1).unwrap();
+        let drawn = fb.drawn();
+        assert_eq!(drawn.len(), 48);
+        assert!(drawn.iter().all(|&(x, y)| x == y));
+    }
+
+    #[test]
+    fn far_endpoints() {
+        let mut fb = Framebuffer::new();
+        line(&mut fb, (-(1 << 24), 10), (1 << 24, 10), 3, 1).unwrap();
+        line(&mut fb, (i32::MIN, 30), (i32::MAX, 30), 1, 1).unwrap();
+        line(&mut fb, (i32::MIN, i32::MIN), (i32::MAX, i32::MAX), 5, 1).unwrap();
+        let drawn = fb.drawn();
+        assert!((9..=11).chain(30..=30).all(|y| (0..64).all(|x| drawn.contains(&(x, y)))));
+        assert!(drawn.contains(&(0, 0)) && drawn.contains(&(47, 47)));
+    }
+
+    #[test]
+    fn clipped_thick_line_keeps_its_course() {
+        let mut clipped = Framebuffer::new();
+        line(&mut clipped, (-1000, -500), (1000, 500), 4, 1).unwrap();
+        // ends just far enough off the display not to be clipped, and for the caps not to show
+        let mut whole = Framebuffer::new();
+        line(&mut whole, (-4, -2), (66, 33), 4, 1).unwrap();
+        assert!(clipped.pixels == whole.pixels);
+        assert_eq!(clipped.get(30, 15), 1);
+    }
+
+    #[test]
+    fn polygon_with_many_points() {
+        // a rectangle with a point on every pixel of its outline
+        let (left, top, right, bottom) = (5, 4, 45, 30);
+        let outline: Vec<Point> = (left..right)
+            .map(|x| (x, top))
+            .chain((top..bottom).map(|y| (right, y)))
+            .chain((left + 1..=right).rev().map(|x| (x, bottom)))
+            .chain((top + 1..=bottom).rev().map(|y| (left, y)))
+            .collect();
+        assert_eq!(outline.len(), 132);
+        let mut polygon = Framebuffer::new();
+        fill_polygon(&mut polygon, &outline, 1).unwrap();
+        let mut rectangle = Framebuffer::new();
+        fill_rectangle(&mut rectangle, (left, top), (40, 26), 1).unwrap();
+        assert!(polygon.pixels == rectangle.pixels);
+    }
+}
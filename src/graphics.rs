@@ -0,0 +1,466 @@
+use core::{
+    convert::TryFrom,
+    marker::PhantomData,
+    ops::{RangeBounds, RangeInclusive},
+    slice::{ChunksExact, Iter},
+};
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    pixelcolor::{raw::RawU16, IntoStorage, Rgb565},
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
+use ssd1963::{display::ReadArea, Bounds, Display, Screen};
+
+use crate::term::vertical_scroller::{fill_exposed, moved_area, Scroller};
+
+// pixels of a horizontal run sent through a single window by `draw_iter`
+const RUN_LEN: usize = 32;
+
+// `embedded-graphics` on top of the display, borrows it like `Term` does, so both can draw in turns
+pub struct Canvas<'me, Disp: Display> {
+    display: &'me mut Disp,
+}
+
+impl<'me, Disp> Canvas<'me, Disp>
+where
+    Disp: Display<Color = u16>,
+{
+    pub fn new(display: &'me mut Disp) -> Self {
+        Self { display }
+    }
+
+    pub fn display(&mut self) -> &mut Disp {
+        self.display
+    }
+
+    // the part of `area` on the display
+    fn window(&self, area: &Rectangle) -> Option<Bounds> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = area.bottom_right()?;
+        Some(Bounds {
+            x_start: u16::try_from(area.top_left.x).unwrap(),
+            x_end: u16::try_from(bottom_right.x).unwrap(),
+            y_start: u16::try_from(area.top_left.y).unwrap(),
+            y_end: u16::try_from(bottom_right.y).unwrap(),
+        })
+    }
+
+    fn flush_run(&mut self, x: u16, y: u16, run: &[u16]) -> Result<(), Disp::Error> {
+        if run.is_empty() {
+            return Ok(());
+        }
+        let x_end = x + u16::try_from(run.len()).unwrap() - 1;
+        self.display.fill_area(x..=x_end, y..=y, &mut run.iter().copied())
+    }
+}
+
+impl<'me, Disp> OriginDimensions for Canvas<'me, Disp>
+where
+    Disp: Display<Color = u16>,
+{
+    fn size(&self) -> Size {
+        Size::new(u32::from(Disp::WIDTH), u32::from(Disp::HEIGHT))
+    }
+}
+
+impl<'me, Disp> DrawTarget for Canvas<'me, Disp>
+where
+    Disp: Display<Color = u16>,
+{
+    type Color = Rgb565;
+    type Error = Disp::Error;
+
+    // pixels next to each other on a line are sent as one window, everything off the display is dropped
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut run = [0u16; RUN_LEN];
+        let (mut run_len, mut run_x, mut run_y) = (0, 0, 0);
+        for Pixel(point, color) in pixels {
+            let (x, y) = match (u16::try_from(point.x), u16::try_from(point.y)) {
+                (Ok(x), Ok(y)) if x < Disp::WIDTH && y < Disp::HEIGHT => (x, y),
+                _ => continue,
+            };
+            let next_x = run_x + u16::try_from(run_len).unwrap();
+            if run_len == RUN_LEN || run_len > 0 && (y != run_y || x != next_x) {
+                self.flush_run(run_x, run_y, &run[..run_len])?;
+                run_len = 0;
+            }
+            if run_len == 0 {
+                run_x = x;
+                run_y = y;
+            }
+            run[run_len] = color.into_storage();
+            run_len += 1;
+        }
+        self.flush_run(run_x, run_y, &run[..run_len])
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let window = match self.window(area) {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+        let visible = area.intersection(&self.bounding_box());
+        let mut colors = area
+            .points()
+            .zip(colors)
+            .filter(|(point, _)| visible.contains(*point))
+            .map(|(_, color)| color.into_storage());
+        self.display.fill_area(window.range_horiz(), window.range_vert(), &mut colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        match self.window(area) {
+            Some(window) => self
+                .display
+                .fill_area_color(window.range_horiz(), window.range_vert(), color.into_storage()),
+            None => Ok(()),
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.fill_area_color(.., .., color.into_storage())
+    }
+}
+
+fn screen_size<S: Screen>() -> Bounds {
+    Bounds {
+        x_start: 0,
+        x_end: S::WIDTH - 1,
+        y_start: 0,
+        y_end: S::HEIGHT - 1,
+    }
+}
+
+fn rectangle(bounds: &Bounds) -> Rectangle {
+    Rectangle::new(
+        Point::new(i32::from(bounds.x_start), i32::from(bounds.y_start)),
+        Size::new(u32::from(bounds.width()), u32::from(bounds.height())),
+    )
+}
+
+fn rgb565(color: u16) -> Rgb565 {
+    Rgb565::from(RawU16::new(color))
+}
+
+// the other way around, any `embedded-graphics` display as a `Display` of the size of `S`, so `Term`
+// runs on it; colors are RGB565 like on the SSD1963 and converted to the target's colors
+pub struct TargetDisplay<Target, S> {
+    target: Target,
+    _screen: PhantomData<S>,
+}
+
+impl<Target, S> TargetDisplay<Target, S>
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    // panics if `target` is smaller than `S`
+    pub fn new(target: Target, _screen: S) -> Self {
+        let size = target.bounding_box().size;
+        assert!(size.width >= u32::from(S::WIDTH) && size.height >= u32::from(S::HEIGHT));
+        Self {
+            target,
+            _screen: PhantomData,
+        }
+    }
+
+    // keeps a copy of the pixels in `buffer`, at least `S::WIDTH * S::HEIGHT` long, so they can be read
+    // back by `CopyScroller` or moved by `BufferScroller`; clears both to black
+    pub fn framebuffer<'fb>(self, buffer: &'fb mut [u16]) -> Result<BufferedDisplay<'fb, Target, S>, Target::Error> {
+        BufferedDisplay::new(self, buffer)
+    }
+
+    pub fn target(&mut self) -> &mut Target {
+        &mut self.target
+    }
+
+    pub fn release(self) -> Target {
+        self.target
+    }
+}
+
+// panics if the area is not on the display, like `Term` does with its dimensions
+impl<Target, S> Display for TargetDisplay<Target, S>
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    type Color = u16;
+    type Error = Target::Error;
+    const WIDTH: u16 = S::WIDTH;
+    const HEIGHT: u16 = S::HEIGHT;
+
+    fn fill_area<X, Y, I>(&mut self, x: X, y: Y, iter: &mut I) -> Result<(), Self::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+        I: Iterator<Item = Self::Color>,
+    {
+        let area = rectangle(&Bounds::new_within(x, y, &screen_size::<S>()).unwrap());
+        self.target.fill_contiguous(&area, iter.map(|color| rgb565(color).into()))
+    }
+
+    fn fill_area_color<X, Y>(&mut self, x: X, y: Y, color: Self::Color) -> Result<(), Self::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let area = rectangle(&Bounds::new_within(x, y, &screen_size::<S>()).unwrap());
+        self.target.fill_solid(&area, rgb565(color).into())
+    }
+}
+
+// `TargetDisplay` with a copy of the pixels in RAM, so it can be read from
+pub struct BufferedDisplay<'fb, Target, S> {
+    display: TargetDisplay<Target, S>,
+    buffer: &'fb mut [u16],
+}
+
+impl<'fb, Target, S> BufferedDisplay<'fb, Target, S>
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    fn new(mut display: TargetDisplay<Target, S>, buffer: &'fb mut [u16]) -> Result<Self, Target::Error> {
+        let buffer = &mut buffer[..usize::from(S::WIDTH) * usize::from(S::HEIGHT)];
+        for pixel in buffer.iter_mut() {
+            *pixel = 0;
+        }
+        display.fill_area_color(.., .., 0)?;
+        Ok(Self { display, buffer })
+    }
+
+    fn row(&mut self, y: u16, bounds: &Bounds) -> &mut [u16] {
+        let start = usize::from(y) * usize::from(S::WIDTH);
+        &mut self.buffer[start + usize::from(bounds.x_start)..=start + usize::from(bounds.x_end)]
+    }
+
+    // draws `bounds` from the buffer
+    fn flush(&mut self, bounds: &Bounds) -> Result<(), Target::Error> {
+        let (buffer, width) = (&*self.buffer, usize::from(S::WIDTH));
+        let mut colors = bounds.range_vert().flat_map(move |y| {
+            let start = usize::from(y) * width;
+            buffer[start + usize::from(bounds.x_start)..=start + usize::from(bounds.x_end)]
+                .iter()
+                .copied()
+        });
+        self.display.fill_area(bounds.range_horiz(), bounds.range_vert(), &mut colors)
+    }
+
+    pub fn release(self) -> (TargetDisplay<Target, S>, &'fb mut [u16]) {
+        (self.display, self.buffer)
+    }
+}
+
+impl<'fb, Target, S> Display for BufferedDisplay<'fb, Target, S>
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    type Color = u16;
+    type Error = Target::Error;
+    const WIDTH: u16 = S::WIDTH;
+    const HEIGHT: u16 = S::HEIGHT;
+
+    // the buffer is updated first, then the whole area is drawn from it
+    fn fill_area<X, Y, I>(&mut self, x: X, y: Y, iter: &mut I) -> Result<(), Self::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+        I: Iterator<Item = Self::Color>,
+    {
+        let bounds = Bounds::new_within(x, y, &screen_size::<S>()).unwrap();
+        for y in bounds.range_vert() {
+            for (pixel, color) in self.row(y, &bounds).iter_mut().zip(&mut *iter) {
+                *pixel = color;
+            }
+        }
+        self.flush(&bounds)
+    }
+
+    fn fill_area_color<X, Y>(&mut self, x: X, y: Y, color: Self::Color) -> Result<(), Self::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let bounds = Bounds::new_within(x, y, &screen_size::<S>()).unwrap();
+        for y in bounds.range_vert() {
+            for pixel in self.row(y, &bounds).iter_mut() {
+                *pixel = color;
+            }
+        }
+        self.display.fill_area_color(bounds.range_horiz(), bounds.range_vert(), color)
+    }
+}
+
+// the pixels of an area of the buffer of a `BufferedDisplay`, row by row
+pub struct BufferArea<'a, E> {
+    rows: ChunksExact<'a, u16>,
+    columns: RangeInclusive<usize>,
+    row: Iter<'a, u16>,
+    _error: PhantomData<E>,
+}
+
+impl<'a, E> Iterator for BufferArea<'a, E> {
+    type Item = Result<u16, E>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&pixel) = self.row.next() {
+                return Some(Ok(pixel));
+            }
+            self.row = self.rows.next()?[self.columns.clone()].iter();
+        }
+    }
+}
+
+// read from the copy in RAM, draw targets can't be read
+impl<'fb, Target, S> ReadArea for BufferedDisplay<'fb, Target, S>
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    type AreaIter<'a>
+        = BufferArea<'a, Target::Error>
+    where
+        Self: 'a;
+
+    fn read_area<X, Y>(&mut self, x: X, y: Y) -> Result<BufferArea<'_, Target::Error>, Target::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let bounds = Bounds::new_within(x, y, &screen_size::<S>()).unwrap();
+        let width = usize::from(S::WIDTH);
+        let rows = &self.buffer[usize::from(bounds.y_start) * width..(usize::from(bounds.y_end) + 1) * width];
+        Ok(BufferArea {
+            rows: rows.chunks_exact(width),
+            columns: usize::from(bounds.x_start)..=usize::from(bounds.x_end),
+            row: [].iter(),
+            _error: PhantomData,
+        })
+    }
+}
+
+// scrolls a `BufferedDisplay` by moving the pixels within its buffer and drawing the moved area from it,
+// without the second buffer a `CopyScroller` needs
+pub struct BufferScroller;
+
+impl<'fb, Target, S> Scroller<BufferedDisplay<'fb, Target, S>> for BufferScroller
+where
+    Target: DrawTarget,
+    Target::Color: From<Rgb565>,
+    S: Screen,
+{
+    fn scroll_area<X, Y>(
+        &mut self,
+        disp: &mut BufferedDisplay<'fb, Target, S>,
+        x: X,
+        y: Y,
+        horiz_by: i16,
+        vert_by: i16,
+        background: Option<u16>,
+    ) -> Result<(), Target::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let area = Bounds::new_within(x, y, &screen_size::<S>()).unwrap();
+        let (source, target) = match moved_area::<BufferedDisplay<'fb, Target, S>>(&area, horiz_by, vert_by) {
+            Some(moved) => moved,
+            None => return background.map_or(Ok(()), |color| fill_exposed(disp, &area, None, color)),
+        };
+        // like memmove: when moving down, start with the last line, so no line is overwritten before it is moved
+        let width = usize::from(S::WIDTH);
+        let (start, end) = (usize::from(source.x_start), usize::from(source.x_end));
+        let buffer = &mut *disp.buffer;
+        let mut move_line = |(from, to): (u16, u16)| {
+            let from = usize::from(from) * width;
+            buffer.copy_within(from + start..=from + end, usize::from(to) * width + usize::from(target.x_start));
+        };
+        let lines = source.range_vert().zip(target.range_vert());
+        if vert_by > 0 {
+            lines.rev().for_each(&mut move_line);
+        } else {
+            lines.for_each(&mut move_line);
+        }
+        disp.flush(&target)?;
+        background.map_or(Ok(()), |color| fill_exposed(disp, &area, Some(&target), color))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::convert::Infallible;
+    use ssd1963::Lcd800x480;
+    use std::{vec, vec::Vec};
+
+    use super::*;
+    use crate::term::vertical_scroller::CopyScroller;
+
+    // the pixels of a display in RAM that can't be read back, like most draw targets
+    struct Pixels(Vec<Rgb565>);
+
+    impl OriginDimensions for Pixels {
+        fn size(&self) -> Size {
+            Size::new(800, 480)
+        }
+    }
+
+    impl DrawTarget for Pixels {
+        type Color = Rgb565;
+        type Error = Infallible;
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+        where
+            I: IntoIterator<Item = Pixel<Rgb565>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.0[usize::try_from(point.y * 800 + point.x).unwrap()] = color;
+            }
+            Ok(())
+        }
+    }
+
+    fn buffered(buffer: &mut [u16]) -> BufferedDisplay<'_, Pixels, Lcd800x480> {
+        let mut disp = TargetDisplay::new(Pixels(vec![rgb565(0); 800 * 480]), Lcd800x480)
+            .framebuffer(buffer)
+            .unwrap();
+        disp.fill_area_color(10..50, 100..120, 0xf800).unwrap();
+        disp.fill_area_color(20..30, 110..=110, 0x07e0).unwrap();
+        disp
+    }
+
+    #[test]
+    fn copy_scroller_reads_the_buffer() {
+        let (mut copied, mut moved) = (vec![0; 800 * 480], vec![0; 800 * 480]);
+        let (mut copied, mut moved) = (buffered(&mut copied), buffered(&mut moved));
+        let mut scroll_buffer = vec![0; 800];
+        CopyScroller::new(&mut scroll_buffer)
+            .scroll_area(&mut copied, 0..100, 50..250, 0, -30, Some(0x001f))
+            .unwrap();
+        BufferScroller.scroll_area(&mut moved, 0..100, 50..250, 0, -30, Some(0x001f)).unwrap();
+        let (copied, moved) = (copied.release().0.release(), moved.release().0.release());
+        assert!(copied.0 == moved.0);
+        let pixel = |x: usize, y: usize| copied.0[y * 800 + x];
+        assert_eq!(pixel(10, 70), rgb565(0xf800));
+        assert_eq!(pixel(20, 80), rgb565(0x07e0));
+        assert_eq!(pixel(10, 90), rgb565(0));
+        // the rows the content left
+        assert_eq!(pixel(10, 249), rgb565(0x001f));
+    }
+}
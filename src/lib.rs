@@ -0,0 +1,7 @@
+#![cfg_attr(not(test), no_std)]
+
+pub mod bitmap;
+pub mod draw;
+pub mod graphics;
+pub mod segments;
+pub mod term;
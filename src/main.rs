@@ -1,25 +1,42 @@
 #![no_main]
 #![no_std]
 
-mod term;
+#[cfg(feature = "bench")]
+mod bench;
 
 use core::marker::PhantomData;
 
 use cortex_m_rt::entry;
+use display::{draw, graphics, term};
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Point, Size},
+    pixelcolor::{IntoStorage, Rgb565},
+    primitives::Rectangle,
+};
 
-use embedded_hal::digital::v2::OutputPin;
 use hal::{
     delay::Delay,
     gpio::gpiob::Parts,
     pac::{CorePeripherals, Peripherals},
     prelude::*,
 };
+use image565::Image;
 use panic_semihosting as _;
 use ssd1963::{GpioReadWrite16BitInterface, Screen};
 use stm32f1xx_gpio16bit::RwPortB;
 use stm32f1xx_hal as hal;
 
-use crate::term::{font::ThisFont, vertical_scroller::CopyScroller, Term};
+use crate::{
+    graphics::Canvas,
+    term::{
+        font::{Heading, MonoFont, ThisFont},
+        glyph_cache::GlyphCache,
+        text::{draw_text, text_height},
+        vertical_scroller::CopyScroller,
+        Term,
+    },
+};
 
 #[entry]
 fn main() -> ! {
@@ -66,21 +83,21 @@ fn main() -> ! {
     } = dp.GPIOB.split(&mut rcc.apb2);
     let (_, pb3, pb4) = afio.mapr.disable_jtag(gpioa.pa15, pb3, pb4);
 
-    let interface = GpioReadWrite16BitInterface::new(
+    let mut interface = GpioReadWrite16BitInterface::new(
         RwPortB::new(
             pb0, pb1, pb2, pb3, pb4, pb5, pb6, pb7, pb8, pb9, pb10, pb11, pb12, pb13, pb14, pb15, crl, crh,
         ),
         gpioa.pa1.into_push_pull_output(&mut gpioa.crl), // DC
         gpioa.pa2.into_push_pull_output(&mut gpioa.crl), // WR
         gpioa.pa3.into_push_pull_output(&mut gpioa.crl), // RD
-    );
+    )
+    .with_cs(gpioa.pa4.into_push_pull_output(&mut gpioa.crl)) // LCD_CS
+    .unwrap()
+    .with_reset(gpioa.pa0.into_push_pull_output(&mut gpioa.crl)) // LCD_RESET
+    .unwrap();
 
-    let mut cs = gpioa.pa4.into_push_pull_output(&mut gpioa.crl); // LCD_CS
-    cs.set_low().unwrap();
-    // let mut rd = gpioa.pa3.into_push_pull_output(&mut gpioa.crl); // LCD_RD
-    // rd.set_high().unwrap();
-    // let mut reset = gpioa.pa0.into_push_pull_output(&mut gpioa.crl); // LCD_RESET
-    // reset.set_high().unwrap();
+    let mut delay = Delay::new(cp.SYST, clocks);
+    interface.hard_reset(&mut delay).unwrap();
 
     struct Gradient<Lcd: Screen> {
         line: u16,
@@ -120,13 +137,23 @@ fn main() -> ! {
             Some(red | green | blue)
         }
     }
-    let mut disp = ssd1963::Ssd1963::new(ssd1963::Lcd800x480, interface, Delay::new(cp.SYST, clocks)).unwrap();
+    let mut disp = ssd1963::Ssd1963::new(ssd1963::Lcd800x480, interface, delay).unwrap();
     disp.fill_area_color(.., .., 0).unwrap();
     // disp.fill_area(.., .., &mut Gradient::<Lcd800x480>::new()).unwrap();
 
-    let mut buffer = [0u16; 9000];
-    let scroller = CopyScroller::new(&mut buffer);
-    // scroller.scroll_area(&mut disp, 0..100, 100..479, 100, -100).unwrap();
+    // a little smaller than the RAM left would allow, to make room for the glyph slots below
+    let mut buffer = [0u16; 8800];
+    // borrows the scroller's buffer for the glyph cache, there is no RAM to spare
+    #[cfg(feature = "bench")]
+    bench::run(&mut disp, &mut buffer, cp.DCB, cp.DWT);
+    // most of it for the scroller, the rest holds the glyphs of the text on the display
+    let mut glyph_slots = [None; 32];
+    let glyph_len = usize::from(ThisFont::CHAR_WIDTH) * usize::from(ThisFont::CHAR_HEIGHT);
+    let scroll_len = buffer.len() - glyph_slots.len() * glyph_len;
+    let (scroll_buffer, glyph_pixels) = buffer.split_at_mut(scroll_len);
+    let glyph_cache = GlyphCache::new(&ThisFont, glyph_pixels, &mut glyph_slots);
+    let scroller = CopyScroller::new(scroll_buffer);
+    // scroller.scroll_area(&mut disp, 0..100, 100..479, 100, -100, None).unwrap();
 
     // disp.fill_area_color(0..480, 380..=380, 0b11111100000).unwrap();
     // let mut x: u16 = 0;
@@ -138,7 +165,29 @@ fn main() -> ! {
     // use core::convert::TryFrom;
     // disp.fill_area_color(.., .., 0).unwrap();
 
-    let mut term = Term::new(&mut disp, &ThisFont, scroller).dimensions(.., 8..);
+    // header bar with the logo above the terminal
+    let logo = Image::new(image565::include_image!("logo")).unwrap();
+    let mut canvas = Canvas::new(&mut disp);
+    let header = Rectangle::new(Point::zero(), Size::new(canvas.size().width, u32::from(logo.height())));
+    // the logo's transparent pixels are blended over this, see its file name
+    let header_color = Rgb565::new(0, 0, 16);
+    canvas.fill_solid(&header, header_color).unwrap();
+    draw::image(&mut disp, (0, 0), &logo).unwrap();
+    let (title, title_font) = ("ssd1963 terminal", Heading);
+    let title_top = (logo.height() - text_height(&title_font, title)) / 2;
+    draw_text(
+        &mut disp,
+        &title_font,
+        (i32::from(logo.width()) + 4, i32::from(title_top)),
+        title,
+        0xffff,
+        Some(header_color.into_storage()),
+    )
+    .unwrap();
+
+    let mut term = Term::new(&mut disp, &ThisFont, scroller)
+        .dimensions(.., logo.height()..)
+        .glyph_cache(glyph_cache);
     use core::fmt::Write;
     for i in 0..100 {
         writeln!(&mut term, "{:3} Hello, world!", i).unwrap();
@@ -0,0 +1,398 @@
+// big numeric readouts drawn as seven (or fourteen) segment digits; every segment is a polygon in
+// either the on or the off color, and a digit only repaints the segments that changed since the
+// previous update, so a value ticking over costs a few windows instead of the whole readout
+use core::convert::TryFrom;
+
+use ssd1963::Display;
+
+use crate::draw::{self, Point};
+
+// a bit for each segment of a digit; the seven segment middle is `G1`, with `G1 | G2` in the masks so
+// either kind can show them, `H` to `M` are the diagonals and center verticals of fourteen segments
+//
+//  --A--
+// |\ | /|
+// F H I J B
+// |  \|/  |
+//  -G1 G2-
+// |  /|\  |
+// E K L M C
+// |/  |  \|
+//  --D--  DP
+pub const A: u16 = 1 << 0;
+pub const B: u16 = 1 << 1;
+pub const C: u16 = 1 << 2;
+pub const D: u16 = 1 << 3;
+pub const E: u16 = 1 << 4;
+pub const F: u16 = 1 << 5;
+pub const G1: u16 = 1 << 6;
+pub const G2: u16 = 1 << 7;
+pub const H: u16 = 1 << 8;
+pub const I: u16 = 1 << 9;
+pub const J: u16 = 1 << 10;
+pub const K: u16 = 1 << 11;
+pub const L: u16 = 1 << 12;
+pub const M: u16 = 1 << 13;
+pub const DP: u16 = 1 << 14;
+
+const G: u16 = G1 | G2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segments {
+    Seven,
+    Fourteen,
+}
+
+impl Segments {
+    // the segments digits of this kind have
+    fn all(self) -> u16 {
+        match self {
+            Segments::Seven => A | B | C | D | E | F | G1 | DP,
+            Segments::Fourteen => A | B | C | D | E | F | G1 | G2 | H | I | J | K | L | M | DP,
+        }
+    }
+
+    // the segments showing `ch`, none for characters that can't be shown
+    pub fn mask(self, ch: char) -> u16 {
+        let mask = match self {
+            Segments::Seven => seven(ch),
+            Segments::Fourteen => fourteen(ch),
+        };
+        mask & self.all()
+    }
+}
+
+fn seven(ch: char) -> u16 {
+    match ch {
+        '0' | 'O' => A | B | C | D | E | F,
+        '1' => B | C,
+        '2' => A | B | D | E | G,
+        '3' => A | B | C | D | G,
+        '4' => B | C | F | G,
+        '5' | 'S' | 's' => A | C | D | F | G,
+        '6' => A | C | D | E | F | G,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' | 'a' => A | B | C | E | F | G,
+        'B' | 'b' => C | D | E | F | G,
+        'C' => A | D | E | F,
+        'c' => D | E | G,
+        'D' | 'd' => B | C | D | E | G,
+        'E' | 'e' => A | D | E | F | G,
+        'F' | 'f' => A | E | F | G,
+        'G' | 'g' => A | C | D | E | F,
+        'H' => B | C | E | F | G,
+        'h' => C | E | F | G,
+        'I' | 'i' => E | F,
+        'J' | 'j' => B | C | D | E,
+        'L' | 'l' => D | E | F,
+        'N' | 'n' => C | E | G,
+        'o' => C | D | E | G,
+        'P' | 'p' => A | B | E | F | G,
+        'R' | 'r' => E | G,
+        'T' | 't' => D | E | F | G,
+        'U' => B | C | D | E | F,
+        'u' => C | D | E,
+        'Y' | 'y' => B | C | D | F | G,
+        '-' => G,
+        '_' => D,
+        '=' => D | G,
+        '°' => A | B | F | G,
+        '.' => DP,
+        _ => 0,
+    }
+}
+
+fn fourteen(ch: char) -> u16 {
+    match ch.to_ascii_uppercase() {
+        '0' => A | B | C | D | E | F | J | K,
+        '1' => B | C | J,
+        '2' => A | B | D | E | G,
+        '3' => A | B | C | D | G2,
+        '4' => B | C | F | G,
+        '5' => A | C | D | F | G,
+        '6' => A | C | D | E | F | G,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' => A | B | C | E | F | G,
+        'B' => A | B | C | D | G2 | I | L,
+        'C' => A | D | E | F,
+        'D' => A | B | C | D | I | L,
+        'E' => A | D | E | F | G1,
+        'F' => A | E | F | G1,
+        'G' => A | C | D | E | F | G2,
+        'H' => B | C | E | F | G,
+        'I' => A | D | I | L,
+        'J' => B | C | D | E,
+        'K' => E | F | G1 | J | M,
+        'L' => D | E | F,
+        'M' => B | C | E | F | H | J,
+        'N' => B | C | E | F | H | M,
+        'O' => A | B | C | D | E | F,
+        'P' => A | B | E | F | G,
+        'Q' => A | B | C | D | E | F | M,
+        'R' => A | B | E | F | G | M,
+        'S' => A | C | D | F | G,
+        'T' => A | I | L,
+        'U' => B | C | D | E | F,
+        'V' => E | F | J | K,
+        'W' => B | C | E | F | K | M,
+        'X' => H | J | K | M,
+        'Y' => H | J | L,
+        'Z' => A | D | J | K,
+        '-' => G,
+        '+' => G | I | L,
+        '*' => G | H | I | J | K | L | M,
+        '/' => J | K,
+        '\\' => H | M,
+        '_' => D,
+        '=' => D | G,
+        '°' => A | B | F | G,
+        '.' => DP,
+        _ => 0,
+    }
+}
+
+// the outline of a segment, `len` of the points are used
+struct Outline {
+    points: [Point; 6],
+    len: usize,
+}
+
+impl Outline {
+    // along x from `x0` to `x1` in the rows `y..y + thickness`, pointed at both ends
+    fn horizontal(x0: i32, x1: i32, y: i32, thickness: i32) -> Self {
+        let half = thickness / 2;
+        Self {
+            points: [
+                (x0, y + half),
+                (x0 + half, y),
+                (x1 - half, y),
+                (x1, y + half),
+                (x1 - half, y + thickness),
+                (x0 + half, y + thickness),
+            ],
+            len: 6,
+        }
+    }
+
+    // along y from `y0` to `y1` in the columns `x..x + thickness`, pointed at both ends
+    fn vertical(x: i32, y0: i32, y1: i32, thickness: i32) -> Self {
+        let half = thickness / 2;
+        Self {
+            points: [
+                (x + half, y0),
+                (x + thickness, y0 + half),
+                (x + thickness, y1 - half),
+                (x + half, y1),
+                (x, y1 - half),
+                (x, y0 + half),
+            ],
+            len: 6,
+        }
+    }
+
+    // from corner to corner of the box `x`, `y`, `width` pixels wide at the top and bottom; `falling`
+    // goes from the top left to the bottom right
+    fn diagonal(x: (i32, i32), y: (i32, i32), width: i32, falling: bool) -> Self {
+        let width = width.min(x.1 - x.0);
+        let (top, bottom) = if falling { (x.0, x.1 - width) } else { (x.1 - width, x.0) };
+        Self {
+            points: [(top, y.0), (top + width, y.0), (bottom + width, y.1), (bottom, y.1), (0, 0), (0, 0)],
+            len: 4,
+        }
+    }
+
+    fn square(x: i32, y: i32, size: i32) -> Self {
+        Self {
+            points: [(x, y), (x + size, y), (x + size, y + size), (x, y + size), (0, 0), (0, 0)],
+            len: 4,
+        }
+    }
+}
+
+// a row of digits `size` pixels big, with segments `thickness` pixels wide; the readout keeps what
+// each digit shows in `shown`, one for every digit, and only draws the segments that change
+//
+// the segments are drawn over whatever is around them, which is left alone; the decimal point of a
+// digit is in the `spacing` after it
+pub struct Readout<'a, Color> {
+    top_left: Point,
+    size: (u16, u16),
+    thickness: u16,
+    spacing: Option<u16>,
+    slant: i16,
+    segments: Segments,
+    on: Color,
+    off: Color,
+    shown: &'a mut [Option<u16>],
+}
+
+impl<'a, Color: Copy> Readout<'a, Color> {
+    // lit segments are `on`, the others `off`; the background color hides the unlit ones
+    pub fn new(top_left: Point, size: (u16, u16), on: Color, off: Color, shown: &'a mut [Option<u16>]) -> Self {
+        shown.iter_mut().for_each(|digit| *digit = None);
+        Self {
+            top_left,
+            size,
+            thickness: (size.0 / 6).max(1),
+            spacing: None,
+            slant: 0,
+            segments: Segments::Seven,
+            on,
+            off,
+            shown,
+        }
+    }
+
+    pub fn thickness(mut self, thickness: u16) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    // pixels between digits, twice the thickness unless given
+    pub fn spacing(mut self, spacing: u16) -> Self {
+        self.spacing = Some(spacing);
+        self
+    }
+
+    // the top of the digits is `slant` pixels to the right of the bottom, to the left if negative
+    pub fn slant(mut self, slant: i16) -> Self {
+        self.slant = slant;
+        self
+    }
+
+    pub fn segments(mut self, segments: Segments) -> Self {
+        self.segments = segments;
+        self
+    }
+
+    // the next `show` draws every segment, after whatever was under the readout has been painted over
+    pub fn invalidate(&mut self) {
+        self.shown.iter_mut().for_each(|digit| *digit = None);
+    }
+
+    // from the left of the readout to the end of the spacing after its last digit, with the slant, at most `u16::MAX`
+    pub fn width(&self) -> u16 {
+        let digits = u32::try_from(self.shown.len()).unwrap_or(u32::MAX);
+        let width = digits.saturating_mul(self.pitch()).saturating_add(u32::from(self.slant.unsigned_abs()));
+        u16::try_from(width).unwrap_or(u16::MAX)
+    }
+
+    // from the left of a digit to the left of the next one
+    fn pitch(&self) -> u32 {
+        u32::from(self.size.0) + self.spacing.map_or(u32::from(self.thickness) * 2, u32::from)
+    }
+
+    fn outline(&self, segment: u16) -> Outline {
+        let (width, height) = (i32::from(self.size.0), i32::from(self.size.1));
+        let thickness = i32::from(self.thickness);
+        let half = thickness / 2;
+        let gap = (thickness / 6).max(1);
+        let spacing = self.spacing.map_or(thickness * 2, i32::from);
+        // left, center and right columns, and top, middle and bottom rows, of segments
+        let (left, center, right) = (0, (width - thickness) / 2, width - thickness);
+        let (top, middle, bottom) = (0, (height - thickness) / 2, height - thickness);
+        // where the pointed ends of the outer segments meet
+        let (x0, x1) = (left + half + gap, right + half - gap);
+        let (y0, y1, y2) = (top + half + gap, middle + half, bottom + half - gap);
+        let (inner_left, inner_right) = ((left + thickness + gap, center - gap), (center + thickness + gap, right - gap));
+        let (inner_top, inner_bottom) = ((top + thickness + gap, middle - gap), (middle + thickness + gap, bottom - gap));
+        match (self.segments, segment) {
+            (_, A) => Outline::horizontal(x0, x1, top, thickness),
+            (_, B) => Outline::vertical(right, y0, y1 - gap, thickness),
+            (_, C) => Outline::vertical(right, y1 + gap, y2, thickness),
+            (_, D) => Outline::horizontal(x0, x1, bottom, thickness),
+            (_, E) => Outline::vertical(left, y1 + gap, y2, thickness),
+            (_, F) => Outline::vertical(left, y0, y1 - gap, thickness),
+            (Segments::Seven, G1) => Outline::horizontal(x0, x1, middle, thickness),
+            (_, G1) => Outline::horizontal(x0, center + half - gap, middle, thickness),
+            (_, G2) => Outline::horizontal(center + half + gap, x1, middle, thickness),
+            (_, H) => Outline::diagonal(inner_left, inner_top, thickness, true),
+            (_, I) => Outline::vertical(center, inner_top.0, inner_top.1, thickness),
+            (_, J) => Outline::diagonal(inner_right, inner_top, thickness, false),
+            (_, K) => Outline::diagonal(inner_left, inner_bottom, thickness, false),
+            (_, L) => Outline::vertical(center, inner_bottom.0, inner_bottom.1, thickness),
+            (_, M) => Outline::diagonal(inner_right, inner_bottom, thickness, true),
+            _ => Outline::square(width + (spacing - thickness) / 2, bottom, thickness),
+        }
+    }
+
+    // `outline` slanted and moved to digit `index`
+    fn place(&self, index: usize, outline: &mut Outline) {
+        let height = i32::from(self.size.1).max(1);
+        let (slant, pitch) = (i32::from(self.slant), i32::try_from(self.pitch()).unwrap());
+        let left = self.top_left.0 + i32::try_from(index).unwrap() * pitch + slant.min(0).abs();
+        for (x, y) in &mut outline.points[..outline.len] {
+            *x += left + (slant * (height - *y) * 2 + height).div_euclid(2 * height);
+            *y += self.top_left.1;
+        }
+    }
+
+    // a character of `text` for each digit, from the first, and blank digits after it; a '.' goes
+    // with the character before it as its decimal point; panics if digits are less than 3 thicknesses
+    // wide or 5 tall
+    pub fn show<Disp: Display<Color = Color>>(&mut self, disp: &mut Disp, text: &str) -> Result<(), Disp::Error> {
+        let thickness = u32::from(self.thickness);
+        assert!(u32::from(self.size.0) >= thickness * 3 && u32::from(self.size.1) >= thickness * 5);
+        let mut chars = text.chars().peekable();
+        for index in 0..self.shown.len() {
+            let mut mask = chars.next().map_or(0, |ch| self.segments.mask(ch));
+            if mask & DP == 0 && chars.next_if_eq(&'.').is_some() {
+                mask |= DP;
+            }
+            let changed = match self.shown[index] {
+                Some(shown) => shown ^ mask,
+                None => self.segments.all(),
+            };
+            let mut segments = changed & self.segments.all();
+            while segments != 0 {
+                let segment = segments & segments.wrapping_neg();
+                segments &= !segment;
+                let mut outline = self.outline(segment);
+                self.place(index, &mut outline);
+                let color = if mask & segment != 0 { self.on } else { self.off };
+                draw::fill_polygon(disp, &outline.points[..outline.len], color)?;
+            }
+            self.shown[index] = Some(mask);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::draw::tests::Framebuffer;
+
+    #[test]
+    fn changed_segments_are_redrawn() {
+        let mut shown = [None; 2];
+        let mut readout = Readout::new((2, 2), (18, 30), 0xffff, 0x0001, &mut shown).slant(4);
+        let mut fb = Framebuffer::new();
+        readout.show(&mut fb, "8.8").unwrap();
+        readout.show(&mut fb, "1.8").unwrap();
+
+        let mut shown = [None; 2];
+        let mut fresh = Framebuffer::new();
+        Readout::new((2, 2), (18, 30), 0xffff, 0x0001, &mut shown)
+            .slant(4)
+            .show(&mut fresh, "1.8")
+            .unwrap();
+        assert!(fb.pixels == fresh.pixels);
+        assert!(fresh.pixels.contains(&0xffff) && fresh.pixels.contains(&0x0001));
+    }
+
+    #[test]
+    fn huge_readouts_saturate() {
+        let mut shown = [None; 3];
+        let readout = Readout::new((0, 0), (u16::MAX, u16::MAX), 0, 0, &mut shown).spacing(u16::MAX);
+        assert_eq!(readout.width(), u16::MAX);
+        let mut outline = readout.outline(DP);
+        readout.place(2, &mut outline);
+    }
+}
@@ -1,7 +1,87 @@
+use core::convert::TryFrom;
+
+use crate::bitmap::{Bitmap1bpp, Depth, GrayBitmap, Order};
+
 pub trait MonoFont {
     const CHAR_WIDTH: u8;
     const CHAR_HEIGHT: u8;
     fn data() -> &'static [u8];
+    // characters outside of the font are drawn as 127
+    fn glyph(ch: char) -> Bitmap1bpp<'static> {
+        let mut ch = u32::from(ch);
+        if !(32..=127).contains(&ch) {
+            ch = 127
+        }
+        let bits_per_char = usize::from(Self::CHAR_HEIGHT) * usize::from(Self::CHAR_WIDTH);
+        let bit_offset = usize::try_from(ch - 32).unwrap() * bits_per_char;
+        Bitmap1bpp::new(
+            Self::data(),
+            u16::from(Self::CHAR_WIDTH),
+            u16::from(Self::CHAR_HEIGHT),
+            Order::ColumnMajor,
+        )
+        .bit_offset(bit_offset)
+    }
+}
+
+// a glyph placed relative to the pen, which is at the top of the line
+#[derive(Clone, Copy)]
+pub struct Glyph<Bitmap = Bitmap1bpp<'static>> {
+    pub bitmap: Bitmap,
+    // from the pen to the left edge of the bitmap
+    pub bearing: i8,
+    // from the top of the line to the top of the bitmap
+    pub top: i8,
+    // from the pen to where the next glyph starts
+    pub advance: u8,
+}
+
+pub trait ProportionalFont {
+    // `Bitmap1bpp` or `GrayBitmap`
+    type Bitmap;
+    const LINE_HEIGHT: u8;
+    // None for characters the font doesn't have
+    fn glyph(ch: char) -> Option<Glyph<Self::Bitmap>>;
+    // added to the advance of `left` when `right` follows it
+    fn kerning(_left: char, _right: char) -> i8 {
+        0
+    }
+}
+
+// for `ProportionalFont::kerning` from a table of pairs
+pub fn kerning_pair(pairs: &[(char, char, i8)], left: char, right: char) -> i8 {
+    pairs
+        .iter()
+        .find(|&&(l, r, _)| l == left && r == right)
+        .map_or(0, |&(_, _, kerning)| kerning)
+}
+
+// a monospaced font with the blank columns around each glyph cut off and one column between glyphs,
+// blank glyphs like the space are half a character wide
+pub struct Trimmed<Font>(pub Font);
+
+impl<Font: MonoFont> ProportionalFont for Trimmed<Font> {
+    type Bitmap = Bitmap1bpp<'static>;
+    const LINE_HEIGHT: u8 = Font::CHAR_HEIGHT;
+    fn glyph(ch: char) -> Option<Glyph> {
+        let cell = Font::glyph(ch);
+        let inked = |&x: &u16| (0..cell.height()).any(|y| cell.get(x, y));
+        let glyph = match ((0..cell.width()).find(inked), (0..cell.width()).rev().find(inked)) {
+            (Some(first), Some(last)) => Glyph {
+                bitmap: cell.crop(first, 0, last - first + 1, cell.height()),
+                bearing: 0,
+                top: 0,
+                advance: u8::try_from(last - first + 2).unwrap(),
+            },
+            _ => Glyph {
+                bitmap: cell.crop(0, 0, 0, cell.height()),
+                bearing: 0,
+                top: 0,
+                advance: Font::CHAR_WIDTH / 2,
+            },
+        };
+        Some(glyph)
+    }
 }
 
 pub struct ThisFont;
@@ -13,6 +93,43 @@ impl MonoFont for ThisFont {
     }
 }
 
+// where a glyph of a font packed by the build script is and how it is placed, the glyphs go from ' '
+// to 127 and every one of them starts on a new byte of the data
+pub struct PackedGlyph {
+    pub offset: u32,
+    pub width: u8,
+    pub height: u8,
+    pub top: u8,
+    pub advance: u8,
+}
+
+fn packed_glyph(glyphs: &'static [PackedGlyph], data: &'static [u8], depth: Depth, ch: char) -> Option<Glyph<GrayBitmap<'static>>> {
+    let packed = glyphs.get(usize::try_from(u32::from(ch).checked_sub(32)?).unwrap())?;
+    let data = &data[usize::try_from(packed.offset).unwrap()..];
+    Some(Glyph {
+        bitmap: GrayBitmap::new(data, u16::from(packed.width), u16::from(packed.height), depth),
+        bearing: 0,
+        top: i8::try_from(packed.top).unwrap(),
+        advance: packed.advance,
+    })
+}
+
+mod heading {
+    use super::{Depth, PackedGlyph};
+    include!(concat!(env!("OUT_DIR"), "/heading.rs"));
+}
+
+// `ThisFont` at twice the size with smoothed diagonals, anti-aliased with 4 bits per pixel; made from
+// assets/fonts/heading.4bpp.png
+pub struct Heading;
+impl ProportionalFont for Heading {
+    type Bitmap = GrayBitmap<'static>;
+    const LINE_HEIGHT: u8 = heading::LINE_HEIGHT;
+    fn glyph(ch: char) -> Option<Glyph<GrayBitmap<'static>>> {
+        packed_glyph(&heading::GLYPHS, &heading::DATA, heading::DEPTH, ch)
+    }
+}
+
 static DATA: [u8; 96 * 8] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //     0
     0x00, 0x00, 0x2f, 0x00, 0x00, 0x00, 0x00, 0x00, // !   1
@@ -0,0 +1,111 @@
+// glyphs already expanded to their colors in caller provided RAM, so the ones in use go to `fill_area`
+// straight from a slice instead of bit by bit from the font; a glyph that isn't cached takes the place
+// of the least recently used one
+use core::{cmp::min, marker::PhantomData};
+
+use super::font::MonoFont;
+
+#[derive(Clone, Copy)]
+pub struct CachedGlyph {
+    ch: char,
+    fg: u16,
+    bg: u16,
+    used: u32,
+}
+
+pub struct GlyphCache<'a, Font> {
+    pixels: &'a mut [u16],
+    slots: &'a mut [Option<CachedGlyph>],
+    clock: u32,
+    _font: PhantomData<Font>,
+}
+
+fn glyph_len<Font: MonoFont>() -> usize {
+    usize::from(Font::CHAR_WIDTH) * usize::from(Font::CHAR_HEIGHT)
+}
+
+impl<'a, Font: MonoFont> GlyphCache<'a, Font> {
+    // as many glyphs as both `pixels` and `slots` have room for; panics if that's not even one
+    pub fn new(_font: &Font, pixels: &'a mut [u16], slots: &'a mut [Option<CachedGlyph>]) -> Self {
+        let count = min(slots.len(), pixels.len() / glyph_len::<Font>());
+        assert!(count > 0);
+        let slots = &mut slots[..count];
+        slots.iter_mut().for_each(|slot| *slot = None);
+        Self {
+            pixels: &mut pixels[..count * glyph_len::<Font>()],
+            slots,
+            clock: 0,
+            _font: PhantomData,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // `ch` in `fg` on `bg` row by row, the pixels `get_bits_transposed` would give
+    pub fn get(&mut self, ch: char, fg: u16, bg: u16) -> &[u16] {
+        if self.clock == u32::MAX {
+            // forget the order rather than let it wrap around
+            self.slots.iter_mut().flatten().for_each(|glyph| glyph.used = 0);
+            self.clock = 0;
+        }
+        self.clock += 1;
+        let len = glyph_len::<Font>();
+        let cached = self
+            .slots
+            .iter()
+            .position(|slot| matches!(slot, Some(glyph) if glyph.ch == ch && glyph.fg == fg && glyph.bg == bg));
+        let index = match cached {
+            Some(index) => index,
+            None => {
+                // empty slots come first
+                let (index, _) = self
+                    .slots
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, slot)| slot.map_or(0, |glyph| glyph.used + 1))
+                    .unwrap();
+                let bits = Font::glyph(ch).bits();
+                for (pixel, bit) in self.pixels[index * len..][..len].iter_mut().zip(bits) {
+                    *pixel = if bit { fg } else { bg };
+                }
+                index
+            }
+        };
+        self.slots[index] = Some(CachedGlyph {
+            ch,
+            fg,
+            bg,
+            used: self.clock,
+        });
+        &self.pixels[index * len..][..len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::term::{font::ThisFont, get_bits_transposed};
+
+    fn expanded(ch: char, fg: u16, bg: u16) -> Vec<u16> {
+        get_bits_transposed(&ThisFont, ch).map(|bit| if bit { fg } else { bg }).collect()
+    }
+
+    #[test]
+    fn cached_glyphs_match_the_font() {
+        let mut pixels = [0; 4 * 64];
+        let mut slots = [None; 4];
+        let mut cache = GlyphCache::new(&ThisFont, &mut pixels, &mut slots);
+        // a cache of 4 glyphs sees every one of them evicted, some colors twice
+        for ch in (0..200).filter_map(char::from_u32).chain("aabbaé".chars()) {
+            for &(fg, bg) in &[(0xffff, 0x0010), (0x07e0, 0), (0xffff, 0x0010)] {
+                assert_eq!(cache.get(ch, fg, bg), &expanded(ch, fg, bg)[..], "{:?}", ch);
+            }
+        }
+    }
+}
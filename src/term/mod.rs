@@ -1,10 +1,20 @@
 pub mod font;
 pub mod fullscreen_scroller;
+pub mod glyph_cache;
+pub mod redraw_scroller;
+pub mod stroke;
+pub mod text;
 pub mod vertical_scroller;
 
 use ssd1963::{Bounds, Display};
 
-use self::{font::MonoFont, fullscreen_scroller::FullscreenVerticalScroller, vertical_scroller::Scroller};
+use self::{
+    font::{MonoFont, ProportionalFont},
+    glyph_cache::GlyphCache,
+    stroke::{StrokeFont, StrokeText},
+    text::GlyphBitmap,
+    vertical_scroller::Scroller,
+};
 use core::{
     convert::{TryFrom, TryInto},
     ops::RangeBounds,
@@ -14,9 +24,9 @@ use core::{
 //     text.chars().flat_map(move |ch| get_bits(_font, ch))
 // }
 
-pub fn get_bits<'font, Font: font::MonoFont>(_font: &'font Font, ch: char) -> impl Iterator<Item = bool> {
+pub fn get_bits<Font: font::MonoFont>(_font: &Font, ch: char) -> impl Iterator<Item = bool> {
     let mut ch = u32::from(ch);
-    if ch < 32 || ch > 127 {
+    if !(32..=127).contains(&ch) {
         ch = 127
     }
     let ch = ch as u8;
@@ -32,21 +42,7 @@ pub fn get_bits<'font, Font: font::MonoFont>(_font: &'font Font, ch: char) -> im
     }
 }
 pub fn get_bits_transposed<'font, Font: font::MonoFont>(_font: &'font Font, ch: char) -> impl Iterator<Item = bool> + 'font {
-    let mut ch = u32::from(ch);
-    if ch < 32 || ch > 127 {
-        ch = 127
-    }
-    let ch = ch as u8;
-    let bits_per_char = u16::from(Font::CHAR_HEIGHT) * u16::from(Font::CHAR_WIDTH);
-    let bit_offset = (u16::from(ch) - 32) * bits_per_char;
-
-    CharPixelTransIter {
-        data: &Font::data(),
-        bit_offset,
-        row: 0,
-        col: 0,
-        _font,
-    }
+    Font::glyph(ch).bits()
 }
 
 pub struct CharPixelIter {
@@ -68,33 +64,7 @@ impl Iterator for CharPixelIter {
         } else {
             self.bit_offset += 1;
         }
-        Some(if bit == 0 { false } else { true })
-    }
-}
-
-pub struct CharPixelTransIter<'font, Font> {
-    data: &'font [u8],
-    bit_offset: u16,
-    row: u8,
-    col: u8,
-    _font: &'font Font,
-}
-impl<'font, Font: MonoFont> Iterator for CharPixelTransIter<'font, Font> {
-    type Item = bool;
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.row == Font::CHAR_WIDTH {
-            self.col += 1;
-            if self.col == Font::CHAR_HEIGHT {
-                return None;
-            }
-            self.row = 0;
-        }
-        let bit_offset = self.bit_offset + u16::from(self.row) * u16::from(Font::CHAR_WIDTH) + u16::from(self.col);
-        self.row += 1;
-        let byte_offset = bit_offset / 8;
-        let bit_offset = bit_offset % 8;
-        let bit = self.data[usize::from(byte_offset)] & (1 << bit_offset);
-        Some(if bit == 0 { false } else { true })
+        Some(bit != 0)
     }
 }
 
@@ -114,9 +84,12 @@ pub struct Term<'me, Disp: Display, Font, Scroller> {
     bgcolor: Disp::Color,
     fgcolor: Disp::Color,
     bounds: Bounds,
+    margin_top: u16,
+    margin_bottom: u16,
     line_offset: u16,
     column_offset: u16,
     start_with_newline: bool,
+    cache: Option<GlyphCache<'me, Font>>,
 }
 
 impl<'me, Disp, Font, Scroll> Term<'me, Disp, Font, Scroll>
@@ -132,27 +105,89 @@ where
             bgcolor: 0u16,
             fgcolor: 0b1111111111111111u16,
             bounds: display_size(display),
+            margin_top: 0,
+            margin_bottom: 0,
             display,
             line_offset: 0,
             column_offset: 0,
             start_with_newline: false,
+            cache: None,
         }
     }
-    // panics if requested dimensions are greater than display size
+    // panics if requested dimensions are greater than display size, or leave no room between the margins
     pub fn dimensions<X, Y>(mut self, x: X, y: Y) -> Self
     where
         X: RangeBounds<u16>,
         Y: RangeBounds<u16>,
     {
         self.bounds = Bounds::new_within(x, y, &display_size(self.display)).unwrap();
+        self.check_margins();
+        self
+    }
+    // rows at the top and bottom of the bounds that are left alone, like the DECSTBM scrolling region;
+    // panics if no line of text fits between them
+    pub fn margins(mut self, top: u16, bottom: u16) -> Self {
+        self.margin_top = top;
+        self.margin_bottom = bottom;
+        self.check_margins();
         self
     }
+    fn check_margins(&self) {
+        let height = u32::from(self.margin_top) + u32::from(self.margin_bottom) + u32::from(Font::CHAR_HEIGHT);
+        assert!(height <= u32::from(self.bounds.height()), "no line of text fits between the margins");
+    }
+    // glyphs are drawn from the cache, for the price of the RAM it takes
+    pub fn glyph_cache(mut self, cache: GlyphCache<'me, Font>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    // for drawing into the margins
+    pub fn display(&mut self) -> &mut Disp {
+        self.display
+    }
+    // text in a proportional font at `x`, `y` within the bounds, off the grid `write` keeps to; it is only
+    // clipped to the display, and only scrolls with a scroller that reads the display back like
+    // `CopyScroller`, the scrollers that redraw what they were told about don't know of it
+    pub fn label<LabelFont>(&mut self, font: &LabelFont, x: u16, y: u16, text: &str) -> Result<(), Disp::Error>
+    where
+        LabelFont: ProportionalFont,
+        LabelFont::Bitmap: GlyphBitmap<u16>,
+    {
+        let top_left = self.within_bounds(x, y);
+        text::draw_text(self.display, font, top_left, text, self.fgcolor, Some(self.bgcolor)).map(|_| ())
+    }
+    // like `label`, in a stroke font
+    pub fn stroke_label<SFont: StrokeFont>(&mut self, stroke: &StrokeText<SFont>, x: u16, y: u16, text: &str) -> Result<(), Disp::Error> {
+        let top_left = self.within_bounds(x, y);
+        stroke.draw(self.display, top_left, text, self.fgcolor, Some(self.bgcolor)).map(|_| ())
+    }
+    fn within_bounds(&self, x: u16, y: u16) -> (i32, i32) {
+        (
+            i32::from(self.bounds.x_start) + i32::from(x),
+            i32::from(self.bounds.y_start) + i32::from(y),
+        )
+    }
+    fn scroll_region(&self) -> Bounds {
+        let mut region = self.bounds;
+        region.y_start += self.margin_top;
+        region.y_end -= self.margin_bottom;
+        region
+    }
     fn scroll_up(&mut self, by: u16) -> Result<(), Disp::Error> {
+        let region = self.scroll_region();
+        if by >= region.height() {
+            return self
+                .scroller
+                .clear_area(self.display, region.range_horiz(), region.range_vert(), self.bgcolor);
+        }
+        // only the lines that stay within the region are moved, the rest is cleared by the scroller
+        let y = region.y_start + by..=region.y_end;
         let by = -i16::try_from(by).unwrap();
         self.scroller
-            .scroll_area(self.display, self.bounds.range_horiz(), self.bounds.range_vert(), 0, by)
+            .scroll_area(self.display, region.range_horiz(), y, 0, by, Some(self.bgcolor))
     }
     pub fn write(&mut self, text: &str) {
+        let region = self.scroll_region();
         let line_len = (Disp::WIDTH / u16::from(Font::CHAR_WIDTH)).try_into().unwrap();
         let mut chars = SplitByLenOrNewline::new(text, line_len);
 
@@ -162,7 +197,7 @@ where
                 Some(CharOrNewline::NewLine) => self.start_with_newline = true,
                 Some(CharOrNewline::Char(c)) => {
                     if self.start_with_newline {
-                        let mut remaininig_area = self.bounds.clone();
+                        let mut remaininig_area = region;
                         remaininig_area.x_start += self.column_offset;
                         remaininig_area.y_start += self.line_offset;
                         remaininig_area.set_height(u16::from(Font::CHAR_HEIGHT));
@@ -175,10 +210,10 @@ where
                             .ok();
 
                         // is there space for another line after this one?
-                        let remaining_height = self.bounds.height() - self.line_offset - u16::from(Font::CHAR_HEIGHT);
+                        let remaining_height = region.height() - self.line_offset - u16::from(Font::CHAR_HEIGHT);
                         self.line_offset = if remaining_height < u16::from(Font::CHAR_HEIGHT) {
                             self.scroll_up(u16::from(Font::CHAR_HEIGHT) - remaining_height).ok();
-                            self.bounds.height() - u16::from(Font::CHAR_HEIGHT)
+                            region.height() - u16::from(Font::CHAR_HEIGHT)
                         } else {
                             self.line_offset + u16::from(Font::CHAR_HEIGHT)
                         };
@@ -186,15 +221,23 @@ where
                         self.column_offset = 0;
                     }
                     let (fg, bg) = (self.fgcolor, self.bgcolor);
-                    let mut bits = get_bits_transposed(self.font, c).map(move |b| if b { fg } else { bg });
                     let end_column_offset = self.column_offset + u16::from(Font::CHAR_WIDTH);
-                    let end_line_offset = self.line_offset + u16::from(Font::CHAR_HEIGHT);
-                    let mut abc = self.bounds.clone();
+                    let mut abc = region;
                     abc.x_start += self.column_offset;
                     abc.y_start += self.line_offset;
                     abc.set_height(u16::from(Font::CHAR_HEIGHT));
                     abc.set_width(u16::from(Font::CHAR_WIDTH));
-                    self.display.fill_area(abc.range_horiz(), abc.range_vert(), &mut bits).ok();
+                    match &mut self.cache {
+                        Some(cache) => {
+                            let mut pixels = cache.get(c, fg, bg).iter().copied();
+                            self.display.fill_area(abc.range_horiz(), abc.range_vert(), &mut pixels).ok();
+                        }
+                        None => {
+                            let mut bits = get_bits_transposed(self.font, c).map(move |b| if b { fg } else { bg });
+                            self.display.fill_area(abc.range_horiz(), abc.range_vert(), &mut bits).ok();
+                        }
+                    }
+                    self.scroller.glyph_drawn(abc.x_start, abc.y_start, c, fg, bg);
                     self.column_offset = end_column_offset - 1;
                 }
             }
@@ -209,7 +252,8 @@ where
     Scroll: Scroller<Disp>,
 {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        Ok(self.write(s))
+        self.write(s);
+        Ok(())
     }
 }
 
@@ -230,10 +274,6 @@ impl<'a> SplitByLenOrNewline<'a> {
             line_offset: 0,
         }
     }
-
-    pub fn line_offset(&self) -> u8 {
-        self.line_offset
-    }
 }
 impl<'a> Iterator for SplitByLenOrNewline<'a> {
     type Item = CharOrNewline;
@@ -0,0 +1,368 @@
+use core::{
+    cmp::{max, min},
+    convert::TryFrom,
+    ops::RangeBounds,
+};
+use ssd1963::{Bounds, Display};
+
+use super::{
+    display_size,
+    font::MonoFont,
+    get_bits_transposed,
+    vertical_scroller::{fill_exposed, moved_area, shift, Scroller},
+};
+
+fn intersection(a: &Bounds, b: &Bounds) -> Option<Bounds> {
+    let bounds = Bounds {
+        x_start: max(a.x_start, b.x_start),
+        x_end: min(a.x_end, b.x_end),
+        y_start: max(a.y_start, b.y_start),
+        y_end: min(a.y_end, b.y_end),
+    };
+    if bounds.x_start > bounds.x_end || bounds.y_start > bounds.y_end {
+        None
+    } else {
+        Some(bounds)
+    }
+}
+
+// for write-only displays, `redraw` gets the display, the area the content was moved to and the
+// offsets, and has to draw it again from whatever model it keeps of the content
+pub struct RedrawScroller<F> {
+    redraw: F,
+}
+impl<F> RedrawScroller<F> {
+    pub fn new(redraw: F) -> Self {
+        Self { redraw }
+    }
+
+    pub fn release(self) -> F {
+        self.redraw
+    }
+}
+
+impl<Disp, F> Scroller<Disp> for RedrawScroller<F>
+where
+    Disp: Display,
+    F: FnMut(&mut Disp, &Bounds, i16, i16) -> Result<(), Disp::Error>,
+{
+    fn scroll_area<X, Y>(
+        &mut self,
+        disp: &mut Disp,
+        x: X,
+        y: Y,
+        horiz_by: i16,
+        vert_by: i16,
+        background: Option<Disp::Color>,
+    ) -> Result<(), Disp::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let area = Bounds::new_within(x, y, &display_size(&*disp)).unwrap();
+        let target = match moved_area::<Disp>(&area, horiz_by, vert_by) {
+            Some((_, target)) => {
+                (self.redraw)(disp, &target, horiz_by, vert_by)?;
+                Some(target)
+            }
+            None => None,
+        };
+        background.map_or(Ok(()), |color| fill_exposed(disp, &area, target.as_ref(), color))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Glyph<Color> {
+    // the glyph's origin can be off the display once only a part of it is left
+    x: i32,
+    y: i32,
+    visible: Bounds,
+    ch: char,
+    fg: Color,
+    bg: Color,
+}
+
+// for write-only displays, keeps the glyphs `Term` draws and draws the part of them that is still visible
+// again at their new position; `glyphs` holds whole lines of the grid `Term` draws on, each glyph goes
+// to the slot of its cell, so a new glyph replaces the one in the same cell or one in the line `glyphs.len()`
+// divided by the display width lines above it
+pub struct GlyphScroller<'a, Disp: Display, Font> {
+    font: &'a Font,
+    glyphs: &'a mut [Option<Glyph<Disp::Color>>],
+    columns: usize,
+    // position of the cell in the first slot, it moves with the glyphs
+    origin: Option<(i32, i32)>,
+}
+impl<'a, Disp: Display, Font: MonoFont> GlyphScroller<'a, Disp, Font> {
+    // panics if `glyphs` can't hold a line of glyphs across the display; `scroll_area` panics unless it
+    // holds every line of the grid the area spans, partial ones included, rather than lose glyphs silently
+    pub fn new(font: &'a Font, glyphs: &'a mut [Option<Glyph<Disp::Color>>]) -> Self {
+        let columns = usize::try_from(i32::from(Disp::WIDTH) / Self::pitch().0).unwrap() + 1;
+        assert!(glyphs.len() >= columns, "`glyphs` must hold at least a line of glyphs");
+        for glyph in glyphs.iter_mut() {
+            *glyph = None;
+        }
+        Self {
+            font,
+            glyphs,
+            columns,
+            origin: None,
+        }
+    }
+
+    // `Term` overlaps neighbouring glyphs by a column
+    fn pitch() -> (i32, i32) {
+        (max(i32::from(Font::CHAR_WIDTH) - 1, 1), i32::from(Font::CHAR_HEIGHT))
+    }
+
+    fn rows(&self) -> usize {
+        self.glyphs.len() / self.columns
+    }
+
+    // the lines wrap around `glyphs`, within a line the slots are in the order `Term` draws the glyphs in
+    fn slot(&mut self, x: i32, y: i32) -> usize {
+        let (origin_x, origin_y) = *self.origin.get_or_insert((x, y));
+        let rows = self.rows();
+        let column = (x - origin_x)
+            .div_euclid(Self::pitch().0)
+            .rem_euclid(i32::try_from(self.columns).unwrap());
+        let row = (y - origin_y).div_euclid(Self::pitch().1).rem_euclid(i32::try_from(rows).unwrap());
+        usize::try_from(row).unwrap() * self.columns + usize::try_from(column).unwrap()
+    }
+
+    fn forget(&mut self, area: &Bounds) {
+        for slot in self.glyphs.iter_mut() {
+            if slot.is_some_and(|glyph| intersection(area, &glyph.visible).is_some()) {
+                *slot = None;
+            }
+        }
+    }
+
+    fn draw(&self, disp: &mut Disp, glyph: &Glyph<Disp::Color>, part: &Bounds) -> Result<(), Disp::Error> {
+        let width = usize::from(Font::CHAR_WIDTH);
+        let (fg, bg) = (glyph.fg, glyph.bg);
+        let (x, y) = (glyph.x, glyph.y);
+        let part_x = i32::from(part.x_start)..=i32::from(part.x_end);
+        let part_y = i32::from(part.y_start)..=i32::from(part.y_end);
+        let mut bits = get_bits_transposed(self.font, glyph.ch)
+            .enumerate()
+            .filter(move |(i, _)| {
+                part_x.contains(&(x + i32::try_from(i % width).unwrap())) && part_y.contains(&(y + i32::try_from(i / width).unwrap()))
+            })
+            .map(move |(_, b)| if b { fg } else { bg });
+        disp.fill_area(part.range_horiz(), part.range_vert(), &mut bits)
+    }
+
+    // fills the parts of `target` no glyph covers, a band of rows crossed by the same glyphs at a time
+    fn fill_gaps(&self, disp: &mut Disp, target: &Bounds, color: Disp::Color) -> Result<(), Disp::Error> {
+        let parts = || self.glyphs.iter().flatten().filter_map(move |glyph| intersection(target, &glyph.visible));
+        let mut y = target.y_start;
+        loop {
+            // up to the row before the next glyph starts or the row the first one ends on
+            let band_end = parts()
+                .map(|part| if part.y_start > y { part.y_start - 1 } else { part.y_end })
+                .filter(|&end| end >= y)
+                .fold(target.y_end, min);
+            let mut x = target.x_start;
+            loop {
+                let next = parts()
+                    .filter(|part| part.y_start <= y && y <= part.y_end && part.x_end >= x)
+                    .min_by_key(|part| part.x_start);
+                let gap_end = next.map_or(target.x_end, |part| part.x_start.saturating_sub(1));
+                if next.is_none_or(|part| part.x_start > x) {
+                    disp.fill_area(x..=gap_end, y..=band_end, &mut core::iter::repeat(color))?;
+                }
+                match next {
+                    Some(part) if part.x_end < target.x_end => x = part.x_end + 1,
+                    _ => break,
+                }
+            }
+            if band_end == target.y_end {
+                return Ok(());
+            }
+            y = band_end + 1;
+        }
+    }
+}
+
+impl<'a, Disp: Display, Font: MonoFont> Scroller<Disp> for GlyphScroller<'a, Disp, Font> {
+    // without a `background` the old pixels around the glyphs stay on the display
+    fn scroll_area<X, Y>(
+        &mut self,
+        disp: &mut Disp,
+        x: X,
+        y: Y,
+        horiz_by: i16,
+        vert_by: i16,
+        background: Option<Disp::Color>,
+    ) -> Result<(), Disp::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let area = Bounds::new_within(x, y, &display_size(&*disp)).unwrap();
+        if let Some((_, origin_y)) = self.origin {
+            let line = |y: u16| (i32::from(y) - origin_y).div_euclid(Self::pitch().1);
+            let lines = usize::try_from(line(area.y_end) - line(area.y_start) + 1).unwrap();
+            assert!(lines <= self.rows(), "`glyphs` must hold a line for every line of the area");
+        }
+        let moved = moved_area::<Disp>(&area, horiz_by, vert_by);
+        // the grid moves with the glyphs, so they keep their slots; glyphs that are not moved are off the grid
+        // afterwards and are forgotten along with the overwritten and cleared ones
+        let source = match &moved {
+            Some((source, _)) => source,
+            None => {
+                self.glyphs.iter_mut().for_each(|slot| *slot = None);
+                self.origin = None;
+                return background.map_or(Ok(()), |color| fill_exposed(disp, &area, None, color));
+            }
+        };
+        for slot in self.glyphs.iter_mut() {
+            *slot = slot.and_then(|mut glyph| {
+                let part = intersection(source, &glyph.visible)?;
+                glyph.x += i32::from(horiz_by);
+                glyph.y += i32::from(vert_by);
+                glyph.visible = Bounds {
+                    x_start: shift(part.x_start, horiz_by),
+                    x_end: shift(part.x_end, horiz_by),
+                    y_start: shift(part.y_start, vert_by),
+                    y_end: shift(part.y_end, vert_by),
+                };
+                Some(glyph)
+            });
+        }
+        if let Some((x, y)) = &mut self.origin {
+            *x += i32::from(horiz_by);
+            *y += i32::from(vert_by);
+        }
+
+        let target = moved.unwrap().1;
+        if let Some(color) = background {
+            self.fill_gaps(disp, &target, color)?;
+        }
+        for glyph in self.glyphs.iter().flatten() {
+            if let Some(part) = intersection(&target, &glyph.visible) {
+                self.draw(disp, glyph, &part)?;
+            }
+        }
+        background.map_or(Ok(()), |color| fill_exposed(disp, &area, Some(&target), color))
+    }
+
+    fn clear_area<X, Y>(&mut self, disp: &mut Disp, x: X, y: Y, background: Disp::Color) -> Result<(), Disp::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        let area = Bounds::new_within(x, y, &display_size(&*disp)).unwrap();
+        self.forget(&area);
+        disp.fill_area(area.range_horiz(), area.range_vert(), &mut core::iter::repeat(background))
+    }
+
+    fn glyph_drawn(&mut self, x: u16, y: u16, ch: char, fg: Disp::Color, bg: Disp::Color) {
+        let visible = Bounds {
+            x_start: x,
+            x_end: x + u16::from(Font::CHAR_WIDTH) - 1,
+            y_start: y,
+            y_end: y + u16::from(Font::CHAR_HEIGHT) - 1,
+        };
+        let (x, y) = (i32::from(x), i32::from(y));
+        let slot = self.slot(x, y);
+        self.glyphs[slot] = Some(Glyph { x, y, visible, ch, fg, bg });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{draw::tests::Framebuffer, term::font::ThisFont};
+
+    // counts the pixels written
+    struct Counting {
+        fb: Framebuffer,
+        written: usize,
+    }
+
+    impl Display for Counting {
+        type Color = u16;
+        type Error = Infallible;
+        const WIDTH: u16 = Framebuffer::WIDTH;
+        const HEIGHT: u16 = Framebuffer::HEIGHT;
+
+        fn fill_area<X, Y, I>(&mut self, x: X, y: Y, iter: &mut I) -> Result<(), Infallible>
+        where
+            X: RangeBounds<u16>,
+            Y: RangeBounds<u16>,
+            I: Iterator<Item = u16>,
+        {
+            let written = &mut self.written;
+            self.fb.fill_area(x, y, &mut iter.inspect(|_| *written += 1))
+        }
+
+        fn fill_area_color<X, Y>(&mut self, x: X, y: Y, color: u16) -> Result<(), Infallible>
+        where
+            X: RangeBounds<u16>,
+            Y: RangeBounds<u16>,
+        {
+            self.fill_area(x, y, &mut core::iter::repeat(color))
+        }
+    }
+
+    // like `Term`, 7 pixels apart so neighbours overlap by a column; line `i` has `i + 3` glyphs
+    fn draw_lines<Disp: Display<Color = u16, Error = Infallible>>(
+        disp: &mut Disp,
+        mut drawn: impl FnMut(u16, u16, char),
+        lines: core::ops::Range<u16>,
+        y_by: i32,
+    ) {
+        for line in lines {
+            for column in 0..line + 3 {
+                let (x, y) = (column * 7, u16::try_from(i32::from(line * 8) + y_by).unwrap());
+                let ch = char::from(b'A' + u8::try_from(line * 9 + column).unwrap() % 26);
+                let mut bits = get_bits_transposed(&ThisFont, ch).map(|b| if b { 1 } else { 2 });
+                disp.fill_area(x..x + 8, y..y + 8, &mut bits).unwrap();
+                drawn(x, y, ch);
+            }
+        }
+    }
+
+    #[test]
+    fn scrolled_glyphs_are_redrawn_over_the_gaps_only() {
+        let mut disp = Counting {
+            fb: Framebuffer::new(),
+            written: 0,
+        };
+        let mut slots = [None; 60];
+        let mut scroller = GlyphScroller::<Counting, _>::new(&ThisFont, &mut slots);
+        let mut glyphs = Vec::new();
+        draw_lines(&mut disp, |x, y, ch| glyphs.push((x, y, ch)), 0..6, 0);
+        for (x, y, ch) in glyphs {
+            scroller.glyph_drawn(x, y, ch, 1, 2);
+        }
+        disp.written = 0;
+        scroller.scroll_area(&mut disp, .., .., 0, -8, Some(3)).unwrap();
+
+        let mut expected = Framebuffer::new();
+        expected.fill_area_color(.., .., 3).unwrap();
+        draw_lines(&mut expected, |_, _, _| {}, 1..6, -8);
+        assert!(disp.fb.pixels == expected.pixels);
+        // every moved glyph once, the rest of each of their lines and the line left at the bottom
+        let glyphs: usize = (1..6).map(|line| line + 3).sum();
+        let gaps: usize = (1..6).map(|line| (64 - (7 * (line + 3) + 1)) * 8).sum();
+        assert_eq!(disp.written, glyphs * 64 + gaps + 64 * 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "`glyphs` must hold a line for every line of the area")]
+    fn too_few_slots_for_the_area() {
+        let mut fb = Framebuffer::new();
+        let mut slots = [None; 50];
+        let mut scroller = GlyphScroller::<Framebuffer, _>::new(&ThisFont, &mut slots);
+        scroller.glyph_drawn(0, 0, 'A', 1, 2);
+        scroller.scroll_area(&mut fb, .., .., 0, -8, Some(3)).unwrap();
+    }
+}
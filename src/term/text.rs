@@ -0,0 +1,117 @@
+// text in a proportional font, laid out by advance and kerning instead of on a grid; a '\n' starts
+// the next line under the first one
+use core::convert::{Infallible, TryFrom};
+
+use ssd1963::Display;
+
+use super::font::{Glyph, ProportionalFont};
+use crate::{
+    bitmap::{Bitmap1bpp, GrayBitmap},
+    draw::{self, Point, Ramp},
+};
+
+// how the glyphs of a font are drawn, with colors worked out once for a whole text
+pub trait GlyphBitmap<Color> {
+    type Colors;
+    // `bg` is already under the glyph when there is one
+    fn colors(fg: Color, bg: Option<Color>) -> Self::Colors;
+    fn draw<Disp: Display<Color = Color>>(&self, disp: &mut Disp, top_left: Point, colors: &Self::Colors) -> Result<(), Disp::Error>;
+}
+
+impl<Color: Copy> GlyphBitmap<Color> for Bitmap1bpp<'_> {
+    type Colors = Color;
+    fn colors(fg: Color, _bg: Option<Color>) -> Color {
+        fg
+    }
+    fn draw<Disp: Display<Color = Color>>(&self, disp: &mut Disp, top_left: Point, fg: &Color) -> Result<(), Disp::Error> {
+        draw::bitmap(disp, top_left, self, *fg, None)
+    }
+}
+
+// blended between `fg` and `bg`, without `bg` the edges can only be left out
+impl GlyphBitmap<u16> for GrayBitmap<'_> {
+    type Colors = Ramp;
+    fn colors(fg: u16, bg: Option<u16>) -> Ramp {
+        match bg {
+            Some(bg) => Ramp::new(fg, bg),
+            None => Ramp::solid(fg),
+        }
+    }
+    fn draw<Disp: Display<Color = u16>>(&self, disp: &mut Disp, top_left: Point, ramp: &Ramp) -> Result<(), Disp::Error> {
+        draw::coverage(disp, top_left, self, ramp)
+    }
+}
+
+// characters the font doesn't have are drawn as '?', or skipped if it doesn't have that either
+fn glyph<Font: ProportionalFont>(ch: char) -> Option<Glyph<Font::Bitmap>> {
+    Font::glyph(ch).or_else(|| Font::glyph('?'))
+}
+
+// calls `place` with every glyph of `line` and the pen position it goes at, returns the pen position
+// after the line
+fn layout<Font, E, F>(line: &str, mut place: F) -> Result<i32, E>
+where
+    Font: ProportionalFont,
+    F: FnMut(i32, &Glyph<Font::Bitmap>) -> Result<(), E>,
+{
+    let mut pen = 0;
+    let mut previous = None;
+    for ch in line.chars() {
+        if let Some(previous) = previous {
+            pen += i32::from(Font::kerning(previous, ch));
+        }
+        if let Some(glyph) = glyph::<Font>(ch) {
+            place(pen, &glyph)?;
+            pen += i32::from(glyph.advance);
+        }
+        previous = Some(ch);
+    }
+    Ok(pen)
+}
+
+fn line_width<Font: ProportionalFont>(line: &str) -> u16 {
+    let pen = layout::<Font, Infallible, _>(line, |_, _| Ok(())).unwrap();
+    u16::try_from(pen.max(0)).unwrap_or(u16::MAX)
+}
+
+// of the widest line, for aligning text before drawing it
+pub fn text_width<Font: ProportionalFont>(_font: &Font, text: &str) -> u16 {
+    text.split('\n').map(line_width::<Font>).max().unwrap_or(0)
+}
+
+pub fn text_height<Font: ProportionalFont>(_font: &Font, text: &str) -> u16 {
+    let lines = u16::try_from(text.split('\n').count()).unwrap_or(u16::MAX);
+    lines.saturating_mul(u16::from(Font::LINE_HEIGHT))
+}
+
+// the glyphs in `fg`, with `bg` the box of each line is filled first; returns the pen position after
+// the last glyph, to carry on from
+pub fn draw_text<Disp, Font>(
+    disp: &mut Disp,
+    _font: &Font,
+    top_left: Point,
+    text: &str,
+    fg: Disp::Color,
+    bg: Option<Disp::Color>,
+) -> Result<Point, Disp::Error>
+where
+    Disp: Display,
+    Font: ProportionalFont,
+    Font::Bitmap: GlyphBitmap<Disp::Color>,
+{
+    let colors = Font::Bitmap::colors(fg, bg);
+    let (x, mut y) = top_left;
+    let mut pen = top_left;
+    for line in text.split('\n') {
+        if let Some(bg) = bg {
+            draw::fill_rectangle(disp, (x, y), (line_width::<Font>(line), u16::from(Font::LINE_HEIGHT)), bg)?;
+        }
+        let end = layout::<Font, _, _>(line, |pen, glyph| {
+            let glyph_top_left = (x + pen + i32::from(glyph.bearing), y + i32::from(glyph.top));
+            glyph.bitmap.draw(disp, glyph_top_left, &colors)
+        })?;
+        pen = (x + end, y);
+        y += i32::from(Font::LINE_HEIGHT);
+    }
+    Ok(pen)
+}
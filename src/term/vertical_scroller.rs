@@ -1,23 +1,106 @@
 use core::convert::TryFrom;
-use core::{cmp::min, ops::RangeBounds};
+use core::{
+    cmp::{max, min},
+    ops::RangeBounds,
+};
 use ssd1963::{display::ReadArea, Bounds, Display};
 
 pub trait Scroller<Disp>
 where
     Disp: Display,
 {
-    fn scroll_area<X, Y>(&mut self, disp: &mut Disp, x: X, y: Y, horiz_by: i16, vert_by: i16) -> Result<(), Disp::Error>
+    // moves the content of the area by the offsets, whatever would end up off the display is dropped;
+    // with a `background`, the part of the area the moved content no longer covers is filled with it
+    fn scroll_area<X, Y>(
+        &mut self,
+        disp: &mut Disp,
+        x: X,
+        y: Y,
+        horiz_by: i16,
+        vert_by: i16,
+        background: Option<Disp::Color>,
+    ) -> Result<(), Disp::Error>
     where
         X: RangeBounds<u16>,
         Y: RangeBounds<u16>;
+
+    // fills the area, scrollers that keep a model of the content forget the part of it in the area
+    fn clear_area<X, Y>(&mut self, disp: &mut Disp, x: X, y: Y, background: Disp::Color) -> Result<(), Disp::Error>
+    where
+        X: RangeBounds<u16>,
+        Y: RangeBounds<u16>,
+    {
+        disp.fill_area(x, y, &mut core::iter::repeat(background))
+    }
+
+    // called by `Term` for every glyph it draws, for scrollers that have to draw the content again
+    fn glyph_drawn(&mut self, _x: u16, _y: u16, _ch: char, _fg: Disp::Color, _bg: Disp::Color) {}
+}
+
+// the part of `start..=end` that stays within `0..limit` when moved by `by`
+fn clip(start: u16, end: u16, by: i16, limit: u16) -> Option<(u16, u16)> {
+    let start = max(i32::from(start), -i32::from(by));
+    let end = min(i32::from(end), i32::from(limit) - 1 - i32::from(by));
+    if start > end {
+        None
+    } else {
+        Some((u16::try_from(start).unwrap(), u16::try_from(end).unwrap()))
+    }
+}
+
+pub(super) fn shift(value: u16, by: i16) -> u16 {
+    u16::try_from(i32::from(value) + i32::from(by)).unwrap()
+}
+
+// the part of `area` that stays on the display when moved by the offsets, and where it ends up
+pub fn moved_area<Disp: Display>(area: &Bounds, horiz_by: i16, vert_by: i16) -> Option<(Bounds, Bounds)> {
+    let (x_start, x_end) = clip(area.x_start, area.x_end, horiz_by, Disp::WIDTH)?;
+    let (y_start, y_end) = clip(area.y_start, area.y_end, vert_by, Disp::HEIGHT)?;
+    let source = Bounds {
+        x_start,
+        x_end,
+        y_start,
+        y_end,
+    };
+    let target = Bounds {
+        x_start: shift(x_start, horiz_by),
+        x_end: shift(x_end, horiz_by),
+        y_start: shift(y_start, vert_by),
+        y_end: shift(y_end, vert_by),
+    };
+    Some((source, target))
+}
+
+// splits `start..=end` into pieces of at most `len`, starting with the last one if `reverse`
+fn chunks(start: u16, end: u16, len: u16, reverse: bool) -> impl Iterator<Item = (u16, u16)> {
+    let count = (end - start) / len + 1;
+    (0..count).map(move |i| {
+        let i = if reverse { count - 1 - i } else { i };
+        let chunk_start = start + i * len;
+        (chunk_start, min(chunk_start + len - 1, end))
+    })
+}
+
+fn fill<Disp: Display>(disp: &mut Disp, x: (u16, u16), y: (u16, u16), color: Disp::Color) -> Result<(), Disp::Error> {
+    disp.fill_area(x.0..=x.1, y.0..=y.1, &mut core::iter::repeat(color))
 }
 
 pub struct CopyScroller<'a, Disp: Display> {
     buffer: &'a mut [Disp::Color],
+    background: Option<Disp::Color>,
 }
 impl<'a, Disp: ReadArea> CopyScroller<'a, Disp> {
+    // panics if `buffer` is empty, a buffer narrower than the area makes horizontal moves slow
     pub fn new(buffer: &'a mut [Disp::Color]) -> Self {
-        Self { buffer }
+        assert!(!buffer.is_empty());
+        Self { buffer, background: None }
+    }
+
+    // fills the part of the area the moved content no longer covers, unless `scroll_area` is given
+    // a background of its own
+    pub fn background(mut self, color: Disp::Color) -> Self {
+        self.background = Some(color);
+        self
     }
 
     fn copy(&mut self, source_window: &Bounds, target_window: &Bounds, disp: &mut Disp) -> Result<(), Disp::Error> {
@@ -42,13 +125,53 @@ impl<'a, Disp: ReadArea> CopyScroller<'a, Disp> {
     }
 }
 
+// fills the part of `area` outside of `target`, which is where scrolled content was moved to
+pub fn fill_exposed<Disp: Display>(disp: &mut Disp, area: &Bounds, target: Option<&Bounds>, color: Disp::Color) -> Result<(), Disp::Error> {
+    let (x, y) = ((area.x_start, area.x_end), (area.y_start, area.y_end));
+    let covered = target.and_then(|target| {
+        let x_covered = (max(area.x_start, target.x_start), min(area.x_end, target.x_end));
+        let y_covered = (max(area.y_start, target.y_start), min(area.y_end, target.y_end));
+        if x_covered.0 > x_covered.1 || y_covered.0 > y_covered.1 {
+            None
+        } else {
+            Some((x_covered, y_covered))
+        }
+    });
+    let (x_covered, y_covered) = match covered {
+        Some(covered) => covered,
+        None => return fill(disp, x, y, color),
+    };
+    if y_covered.0 > y.0 {
+        fill(disp, x, (y.0, y_covered.0 - 1), color)?;
+    }
+    if y_covered.1 < y.1 {
+        fill(disp, x, (y_covered.1 + 1, y.1), color)?;
+    }
+    if x_covered.0 > x.0 {
+        fill(disp, (x.0, x_covered.0 - 1), y_covered, color)?;
+    }
+    if x_covered.1 < x.1 {
+        fill(disp, (x_covered.1 + 1, x.1), y_covered, color)?;
+    }
+    Ok(())
+}
+
 impl<'a, Disp: ReadArea> Scroller<Disp> for CopyScroller<'a, Disp> {
-    fn scroll_area<X, Y>(&mut self, disp: &mut Disp, x: X, y: Y, horiz_by: i16, vert_by: i16) -> Result<(), Disp::Error>
+    fn scroll_area<X, Y>(
+        &mut self,
+        disp: &mut Disp,
+        x: X,
+        y: Y,
+        horiz_by: i16,
+        vert_by: i16,
+        background: Option<Disp::Color>,
+    ) -> Result<(), Disp::Error>
     where
         X: RangeBounds<u16>,
         Y: RangeBounds<u16>,
     {
-        let source = Bounds::new_within(
+        let background = background.or(self.background);
+        let area = Bounds::new_within(
             x,
             y,
             &Bounds {
@@ -59,25 +182,37 @@ impl<'a, Disp: ReadArea> Scroller<Disp> for CopyScroller<'a, Disp> {
             },
         )
         .unwrap();
-        let buffer_lines = u16::try_from(min(usize::from(source.height()), self.buffer.len() / usize::from(source.width()))).unwrap();
-        let mut source_window = source;
-        source_window.set_height(buffer_lines);
-        let mut target_window = source_window;
-        target_window.move_by(horiz_by, vert_by);
+        let (source, target) = match moved_area::<Disp>(&area, horiz_by, vert_by) {
+            Some(moved) => moved,
+            None => return background.map_or(Ok(()), |color| fill_exposed(disp, &area, None, color)),
+        };
 
-        let iterations = source.height() / buffer_lines;
-        for _ in 0..iterations {
-            self.copy(&source_window, &target_window, disp)?;
-            source_window.move_by(0u16, buffer_lines);
-            target_window.move_by(0u16, buffer_lines);
-        }
-        let remaining_lines = source.height() - iterations * source_window.height();
-        if remaining_lines > 0 {
-            source_window.set_height(remaining_lines);
-            target_window.set_height(remaining_lines);
-            self.copy(&source_window, &target_window, disp)?;
+        // whole lines while they fit into the buffer, pieces of a single line otherwise
+        let (chunk_width, chunk_lines) = if self.buffer.len() >= usize::from(source.width()) {
+            let lines = min(usize::from(source.height()), self.buffer.len() / usize::from(source.width()));
+            (source.width(), u16::try_from(lines).unwrap())
+        } else {
+            (u16::try_from(self.buffer.len()).unwrap(), 1)
+        };
+        // like memmove: when moving down or right, start at the far end, so no window is overwritten before it is read
+        for (y_start, y_end) in chunks(source.y_start, source.y_end, chunk_lines, vert_by > 0) {
+            for (x_start, x_end) in chunks(source.x_start, source.x_end, chunk_width, horiz_by > 0) {
+                let source_window = Bounds {
+                    x_start,
+                    x_end,
+                    y_start,
+                    y_end,
+                };
+                let target_window = Bounds {
+                    x_start: shift(x_start, horiz_by),
+                    x_end: shift(x_end, horiz_by),
+                    y_start: shift(y_start, vert_by),
+                    y_end: shift(y_end, vert_by),
+                };
+                self.copy(&source_window, &target_window, disp)?;
+            }
         }
 
-        Ok(())
+        background.map_or(Ok(()), |color| fill_exposed(disp, &area, Some(&target), color))
     }
 }